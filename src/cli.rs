@@ -1,13 +1,56 @@
-use clap::Parser;
-use crate::config::{Config, load_config};
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser};
+use clap::parser::ValueSource;
+use crate::config::{Config, PartialConfig, load_config_for};
 
-#[derive(clap::ValueEnum, Clone, Debug)]
+#[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum ColorChoice {
     Auto,
     Always,
     Never,
 }
 
+#[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundChoice {
+    Auto,
+    Light,
+    Dark,
+}
+
+#[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PagingChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IndentStyle {
+    #[default]
+    Space,
+    Tab,
+}
+
+#[derive(clap::ValueEnum, serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NewlineStyle {
+    #[default]
+    Lf,
+    Crlf,
+    /// Match the line endings of the input.
+    Auto,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    #[default]
+    Human,
+    Json,
+}
+
 #[derive(Parser, Debug)]
 #[command(
     author,
@@ -31,6 +74,36 @@ pub struct CliArgs {
 
     #[arg(long, help = "Run performance benchmarks")]
     pub benchmark: bool,
+
+    #[arg(long, help = "List available themes with a preview of each")]
+    pub list_themes: bool,
+
+    #[arg(long, help = "Print the effective configuration and where each value comes from")]
+    pub print_config: bool,
+
+    #[arg(long, help = "Browse the input in a full-screen viewer with folding and search")]
+    pub interactive: bool,
+
+    #[arg(long, help = "Print the document's shape: its keys and the types seen under them, with arrays of objects merged")]
+    pub shape: bool,
+
+    #[arg(long, conflicts_with = "unflatten", help = "Flatten the document into a single-level object keyed by paths such as a.b[0]")]
+    pub flatten: bool,
+
+    #[arg(long, help = "Rebuild nested objects and arrays from an object keyed by flattened paths")]
+    pub unflatten: bool,
+
+    #[arg(long, value_enum, default_value = "human", help = "How errors are reported on stderr")]
+    pub error_format: ErrorFormat,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Ids of the options given on the command line, as opposed to clap
+    /// defaults. Filled by the `parse_tracked` constructors; empty otherwise,
+    /// in which case every value can be overridden by config.
+    #[arg(skip)]
+    pub explicit: Vec<String>,
 }
 
 #[derive(clap::Args, Debug, Clone)]
@@ -38,10 +111,10 @@ pub struct DisplayArgs {
     #[arg(short = 'i', long, default_value_t = 2)]
     pub indent: usize,
 
-    #[arg(long, default_value_t = true)]
+    #[arg(long, default_value_t = true, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set)]
     pub sort_keys: bool,
 
-    #[arg(short = 'c', long)]
+    #[arg(short = 'c', long, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set)]
     pub compact: bool,
 
     #[arg(long)]
@@ -50,39 +123,186 @@ pub struct DisplayArgs {
     #[arg(long)]
     pub max_string_length: Option<usize>,
 
-    #[arg(long)]
+    #[arg(long, help = "Show only the first N items of each array, followed by how many more there are")]
+    pub max_items: Option<usize>,
+
+    #[arg(long, global = true)]
     pub get: Option<String>,
 
-    #[arg(long)]
+    #[arg(long, global = true, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set)]
     pub raw: bool,
 
-    #[arg(long, default_value = "json", help = "Output format: json, yaml, toml, csv")]
+    #[arg(long, default_value = "json", help = "Output format: json, yaml, toml, csv, html, svg, lines, table, markdown")]
     pub format: String,
 
-    #[arg(long, help = "Input format: json, yaml, toml, csv [default: json]")]
+    #[arg(long, global = true, help = "Input format: json, yaml, toml, csv [default: json]")]
     pub input_format: Option<String>,
 
     #[arg(long, help = "Watch file for changes and reformat on modify")]
     pub watch: Option<String>,
 
-    #[arg(long, value_enum, help = "Color output control")]
+    #[arg(long, value_enum, global = true, help = "Color output control")]
     pub color: Option<ColorChoice>,
 
     #[arg(long, help = "Path to a JSON Schema file for validation")]
     pub schema: Option<String>,
+
+    #[arg(long, global = true, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set, help = "Recover as much as possible from broken or truncated JSON")]
+    pub lenient: bool,
+
+    #[arg(long, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set, help = "Add comments explaining timestamps, byte sizes and base64/JWT strings")]
+    pub annotate: bool,
+
+    #[arg(long, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set, help = "Number the output lines in a gutter")]
+    pub line_numbers: bool,
+
+    #[arg(long, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set, help = "Show the JSON path of each line in the gutter instead of its number")]
+    pub line_paths: bool,
+
+    #[arg(long, help = "Keep arrays and objects on one line when they fit in this many columns; 0 expands them all [default: terminal width]")]
+    pub width: Option<usize>,
+
+    #[arg(long, value_enum, default_value = "space", help = "Indent with spaces or tabs")]
+    pub indent_style: IndentStyle,
+
+    #[arg(long, value_enum, default_value = "lf", help = "Line endings: lf, crlf, or auto to match the input")]
+    pub newline: NewlineStyle,
+
+    #[arg(long, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set, help = "Do not end the output with a newline")]
+    pub no_final_newline: bool,
+
+    #[arg(long, default_value = ".", help = "Separator between keys in --flatten and --unflatten paths")]
+    pub key_separator: String,
+
+    #[arg(long, default_value = "=", help = "Separator between path and value in --format lines")]
+    pub value_separator: String,
+
+    #[arg(long, help = "CSV columns to write, in order, as comma-separated paths like --get (id,user.name,tags[0]) [default: every key, nested objects as dotted names]")]
+    pub columns: Option<String>,
+
+    #[arg(long, default_value = ",", help = "CSV field delimiter: a single character, or tab")]
+    pub delimiter: String,
+
+    #[arg(long, default_value = "\"", help = "CSV quote character")]
+    pub quote: String,
+
+    #[arg(long, default_value_t = false, num_args = 0..=1, require_equals = true, default_missing_value = "true", action = ArgAction::Set, help = "Read CSV without a header row, as one array per row")]
+    pub no_header: bool,
+
+    #[arg(long, help = "Types for CSV input columns, by header name or 0-based index: id=int,zip=string; types are auto, string, int, float, bool [default: auto]")]
+    pub column_types: Option<String>,
+
+    #[arg(long, value_enum, help = "Page output taller than the terminal through $PAGER (less -R by default) [default: auto]")]
+    pub paging: Option<PagingChoice>,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Summarize the structure of a document: node counts, depth, largest
+    /// arrays, longest strings, key frequency and the distinct paths
+    Stats(StatsArgs),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct StatsArgs {
+    /// Input files (use - for stdin)
+    #[arg(name = "FILE", num_args = 0..)]
+    pub files: Vec<String>,
+
+    #[arg(long, help = "Print the statistics as JSON instead of a table")]
+    pub json: bool,
+
+    #[arg(long, default_value_t = 5, help = "How many entries to list for the largest arrays, longest strings, keys and top-level sizes")]
+    pub top: usize,
 }
 
 #[derive(clap::Args, Debug, Clone)]
 pub struct ThemeArgs {
-    #[arg(long, default_value = "default", help = "Color theme: default, solarized, mono, rainbow, ocean, forest, pastel, sakura, cyberpunk, ghibli, evangelion")]
+    #[arg(long, default_value = "default", global = true, help = "Color theme: default, solarized, mono, rainbow, ocean, forest, pastel, sakura, cyberpunk, ghibli, evangelion, or a user theme (see --list-themes)")]
     pub theme: String,
+
+    #[arg(long, value_enum, help = "Terminal background, used to pick light or dark theme variants [default: auto]")]
+    pub background: Option<BackgroundChoice>,
 }
 
 
 
 impl CliArgs {
+    /// Parse the process arguments, recording which options were given explicitly.
+    pub fn parse_tracked() -> Self {
+        Self::from_matches(Self::command().get_matches()).unwrap_or_else(|e| e.exit())
+    }
+
+    pub fn try_parse_tracked_from<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        Self::from_matches(Self::command().try_get_matches_from(args)?)
+    }
+
+    fn from_matches(matches: clap::ArgMatches) -> Result<Self, clap::Error> {
+        let mut args = Self::from_arg_matches(&matches)?;
+        args.explicit = matches.ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(|id| id.to_string())
+            .collect();
+        Ok(args)
+    }
+
+    pub fn is_explicit(&self, id: &str) -> bool {
+        self.explicit.iter().any(|e| e == id)
+    }
+
+    /// The settings that were given explicitly on the command line.
+    pub fn explicit_partial(&self) -> PartialConfig {
+        let d = &self.display;
+        let pick = |id: &str| self.is_explicit(id);
+        PartialConfig {
+            indent: pick("indent").then_some(d.indent),
+            sort_keys: pick("sort_keys").then_some(d.sort_keys),
+            compact: pick("compact").then_some(d.compact),
+            max_depth: if pick("max_depth") { d.max_depth } else { None },
+            max_string_length: if pick("max_string_length") { d.max_string_length } else { None },
+            max_items: if pick("max_items") { d.max_items } else { None },
+            get: if pick("get") { d.get.clone() } else { None },
+            theme: pick("theme").then(|| self.theme_args.theme.clone()),
+            theme_light: None,
+            theme_dark: None,
+            background: if pick("background") { self.theme_args.background } else { None },
+            raw: pick("raw").then_some(d.raw),
+            format: pick("format").then(|| d.format.clone()),
+            input_format: if pick("input_format") { d.input_format.clone() } else { None },
+            color: if pick("color") { d.color.clone() } else { None },
+            schema: if pick("schema") { d.schema.clone() } else { None },
+            lenient: pick("lenient").then_some(d.lenient),
+            annotate: pick("annotate").then_some(d.annotate),
+            line_numbers: pick("line_numbers").then_some(d.line_numbers),
+            paging: if pick("paging") { d.paging } else { None },
+            line_paths: pick("line_paths").then_some(d.line_paths),
+            width: if pick("width") { d.width } else { None },
+            indent_style: pick("indent_style").then_some(d.indent_style),
+            newline: pick("newline").then_some(d.newline),
+            final_newline: pick("no_final_newline").then_some(!d.no_final_newline),
+            key_separator: pick("key_separator").then(|| d.key_separator.clone()),
+            value_separator: pick("value_separator").then(|| d.value_separator.clone()),
+            columns: if pick("columns") { d.columns.clone() } else { None },
+            delimiter: pick("delimiter").then(|| d.delimiter.clone()),
+            quote: pick("quote").then(|| d.quote.clone()),
+            header: pick("no_header").then_some(!d.no_header),
+            column_types: if pick("column_types") { d.column_types.clone() } else { None },
+            themes: None,
+        }
+    }
+
     pub fn to_config(&self) -> Config {
-        match load_config() {
+        self.to_config_for(None)
+    }
+
+    /// The effective config for one input file, including the
+    /// `.editorconfig` settings that apply to it.
+    pub fn to_config_for(&self, file: Option<&std::path::Path>) -> Config {
+        match load_config_for(file) {
             Ok(partial) => Config::merge(self, partial),
             Err(e) => {
                 eprintln!("Config load error: {}", e);
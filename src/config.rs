@@ -1,17 +1,269 @@
-use serde::Deserialize;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use crate::error::JsonfizzError;
 use std::fs;
 
-#[derive(Deserialize, Clone, Debug, Default)]
+/// Setting names in the order `--print-config` lists them. Each is also the
+/// TOML key and, upper-cased with a `JSONFIZZ_` prefix, the environment variable.
+pub const FIELDS: &[&str] = &[
+    "indent",
+    "sort_keys",
+    "compact",
+    "max_depth",
+    "max_string_length",
+    "max_items",
+    "get",
+    "theme",
+    "theme_light",
+    "theme_dark",
+    "background",
+    "raw",
+    "format",
+    "input_format",
+    "color",
+    "schema",
+    "lenient",
+    "annotate",
+    "line_numbers",
+    "paging",
+    "line_paths",
+    "width",
+    "indent_style",
+    "newline",
+    "final_newline",
+    "key_separator",
+    "value_separator",
+    "columns",
+    "delimiter",
+    "quote",
+    "header",
+    "column_types",
+];
+
+const PROJECT_CONFIG_NAME: &str = ".jsonfizz.toml";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct PartialConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub indent: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_keys: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_depth: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_string_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub get: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub theme: Option<String>,
+    /// Theme used instead of `theme` on a light terminal background.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme_light: Option<String>,
+    /// Theme used instead of `theme` on a dark terminal background.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme_dark: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<crate::cli::BackgroundChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<crate::cli::ColorChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub schema: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lenient: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_numbers: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paging: Option<crate::cli::PagingChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_paths: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent_style: Option<crate::cli::IndentStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub newline: Option<crate::cli::NewlineStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_newline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_separator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_separator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_types: Option<String>,
+    /// Themes defined inline as `[themes.<name>]` tables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub themes: Option<BTreeMap<String, crate::theme::ThemeDef>>,
+}
+
+impl PartialConfig {
+    /// Apply every value set in `other` on top of `self`.
+    pub fn overlay(&mut self, other: PartialConfig) {
+        if other.indent.is_some() {
+            self.indent = other.indent;
+        }
+        if other.sort_keys.is_some() {
+            self.sort_keys = other.sort_keys;
+        }
+        if other.compact.is_some() {
+            self.compact = other.compact;
+        }
+        if other.max_depth.is_some() {
+            self.max_depth = other.max_depth;
+        }
+        if other.max_string_length.is_some() {
+            self.max_string_length = other.max_string_length;
+        }
+        if other.max_items.is_some() {
+            self.max_items = other.max_items;
+        }
+        if other.get.is_some() {
+            self.get = other.get;
+        }
+        // A theme set by a higher layer also wins over the pair set below it.
+        if other.theme.is_some() {
+            self.theme = other.theme;
+            self.theme_light = None;
+            self.theme_dark = None;
+        }
+        if other.theme_light.is_some() {
+            self.theme_light = other.theme_light;
+        }
+        if other.theme_dark.is_some() {
+            self.theme_dark = other.theme_dark;
+        }
+        if other.background.is_some() {
+            self.background = other.background;
+        }
+        if other.raw.is_some() {
+            self.raw = other.raw;
+        }
+        if other.format.is_some() {
+            self.format = other.format;
+        }
+        if other.input_format.is_some() {
+            self.input_format = other.input_format;
+        }
+        if other.color.is_some() {
+            self.color = other.color;
+        }
+        if other.schema.is_some() {
+            self.schema = other.schema;
+        }
+        if other.lenient.is_some() {
+            self.lenient = other.lenient;
+        }
+        if other.annotate.is_some() {
+            self.annotate = other.annotate;
+        }
+        if other.line_numbers.is_some() {
+            self.line_numbers = other.line_numbers;
+        }
+        if other.paging.is_some() {
+            self.paging = other.paging;
+        }
+        if other.line_paths.is_some() {
+            self.line_paths = other.line_paths;
+        }
+        if other.width.is_some() {
+            self.width = other.width;
+        }
+        if other.indent_style.is_some() {
+            self.indent_style = other.indent_style;
+        }
+        if other.newline.is_some() {
+            self.newline = other.newline;
+        }
+        if other.final_newline.is_some() {
+            self.final_newline = other.final_newline;
+        }
+        if other.key_separator.is_some() {
+            self.key_separator = other.key_separator;
+        }
+        if other.value_separator.is_some() {
+            self.value_separator = other.value_separator;
+        }
+        if other.columns.is_some() {
+            self.columns = other.columns;
+        }
+        if other.delimiter.is_some() {
+            self.delimiter = other.delimiter;
+        }
+        if other.quote.is_some() {
+            self.quote = other.quote;
+        }
+        if other.header.is_some() {
+            self.header = other.header;
+        }
+        if other.column_types.is_some() {
+            self.column_types = other.column_types;
+        }
+        if let Some(themes) = other.themes {
+            merge_themes(self.themes.get_or_insert_with(BTreeMap::new), themes);
+        }
+    }
+
+    fn to_table(&self) -> toml::value::Table {
+        match toml::Value::try_from(self) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => toml::value::Table::new(),
+        }
+    }
+}
+
+/// Where a configuration value came from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigSource {
+    /// Built-in defaults, used when no layer sets a value.
+    Default,
+    /// `.editorconfig` sections matching this input file.
+    EditorConfig(PathBuf),
+    System(PathBuf),
+    User(PathBuf),
+    Project(PathBuf),
+    Env(String),
+    CommandLine,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::EditorConfig(path) => write!(f, ".editorconfig for {}", path.display()),
+            ConfigSource::System(path) => write!(f, "system config {}", path.display()),
+            ConfigSource::User(path) => write!(f, "user config {}", path.display()),
+            ConfigSource::Project(path) => write!(f, "project config {}", path.display()),
+            ConfigSource::Env(var) => write!(f, "environment {}", var),
+            ConfigSource::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    pub partial: PartialConfig,
 }
 
 #[derive(Clone, Debug)]
@@ -21,13 +273,34 @@ pub struct Config {
     pub compact: bool,
     pub max_depth: Option<usize>,
     pub max_string_length: Option<usize>,
+    pub max_items: Option<usize>,
     pub get: Option<String>,
     pub theme: String,
+    pub theme_light: Option<String>,
+    pub theme_dark: Option<String>,
+    pub background: Option<crate::cli::BackgroundChoice>,
     pub raw: bool,
     pub format: String,
     pub input_format: String,
     pub color: Option<crate::cli::ColorChoice>,
     pub schema: Option<String>,
+    pub lenient: bool,
+    pub annotate: bool,
+    pub line_numbers: bool,
+    pub paging: Option<crate::cli::PagingChoice>,
+    pub line_paths: bool,
+    pub width: Option<usize>,
+    pub indent_style: crate::cli::IndentStyle,
+    pub newline: crate::cli::NewlineStyle,
+    pub final_newline: bool,
+    pub key_separator: String,
+    pub value_separator: String,
+    pub columns: Option<String>,
+    pub delimiter: String,
+    pub quote: String,
+    pub header: bool,
+    pub column_types: Option<String>,
+    pub themes: BTreeMap<String, crate::theme::ThemeDef>,
 }
 
 impl Default for Config {
@@ -38,18 +311,41 @@ impl Default for Config {
             compact: false,
             max_depth: None,
             max_string_length: None,
+            max_items: None,
             get: None,
             theme: "default".to_string(),
+            theme_light: None,
+            theme_dark: None,
+            background: None,
             raw: false,
             format: "json".to_string(),
             input_format: "json".to_string(),
             color: None,
             schema: None,
+            lenient: false,
+            annotate: false,
+            line_numbers: false,
+            paging: None,
+            line_paths: false,
+            width: None,
+            indent_style: crate::cli::IndentStyle::Space,
+            newline: crate::cli::NewlineStyle::Lf,
+            final_newline: true,
+            key_separator: ".".to_string(),
+            value_separator: "=".to_string(),
+            columns: None,
+            delimiter: ",".to_string(),
+            quote: "\"".to_string(),
+            header: true,
+            column_types: None,
+            themes: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
+    /// Build the effective config: clap defaults, then `partial` (the merged
+    /// config files and environment), then the flags given on the command line.
     pub fn merge(cli: &crate::cli::CliArgs, partial: Option<PartialConfig>) -> Self {
         let mut config = Config {
             indent: cli.display.indent,
@@ -57,63 +353,652 @@ impl Config {
             compact: cli.display.compact,
             max_depth: cli.display.max_depth,
             max_string_length: cli.display.max_string_length,
+            max_items: cli.display.max_items,
             get: cli.display.get.clone(),
             theme: cli.theme_args.theme.clone(),
+            theme_light: None,
+            theme_dark: None,
+            background: cli.theme_args.background,
             raw: cli.display.raw,
             format: cli.display.format.clone(),
             input_format: cli.display.input_format.clone().unwrap_or_else(|| "json".to_string()),
             color: cli.display.color.clone(),
             schema: cli.display.schema.clone(),
+            lenient: cli.display.lenient,
+            annotate: cli.display.annotate,
+            line_numbers: cli.display.line_numbers,
+            paging: cli.display.paging,
+            line_paths: cli.display.line_paths,
+            width: cli.display.width,
+            indent_style: cli.display.indent_style,
+            newline: cli.display.newline,
+            final_newline: !cli.display.no_final_newline,
+            key_separator: cli.display.key_separator.clone(),
+            value_separator: cli.display.value_separator.clone(),
+            columns: cli.display.columns.clone(),
+            delimiter: cli.display.delimiter.clone(),
+            quote: cli.display.quote.clone(),
+            header: !cli.display.no_header,
+            column_types: cli.display.column_types.clone(),
+            themes: BTreeMap::new(),
         };
         if let Some(p) = partial {
-            if let Some(v) = p.indent {
-                config.indent = v;
-            }
-            if let Some(v) = p.sort_keys {
-                config.sort_keys = v;
-            }
-            if let Some(v) = p.max_depth {
-                config.max_depth = if v == 0 { None } else { Some(v) };
-            }
-            if let Some(v) = p.max_string_length {
-                config.max_string_length = if v == 0 { None } else { Some(v) };
-            }
-            if let Some(v) = p.theme {
-                config.theme = v;
-            }
-            if let Some(v) = p.format {
-                config.format = v;
-            }
-            if let Some(v) = p.schema {
-                config.schema = Some(v);
-            }
+            config.apply(p);
+        }
+        config.apply(cli.explicit_partial());
+        // A theme named on the command line wins over the configured pair.
+        if cli.is_explicit("theme") {
+            config.theme_light = None;
+            config.theme_dark = None;
         }
         config
     }
+
+    /// The theme for `background`: `theme_light` or `theme_dark` when
+    /// configured, otherwise `theme`.
+    pub fn theme_for(&self, background: crate::theme::Background) -> &str {
+        let variant = match background {
+            crate::theme::Background::Light => &self.theme_light,
+            crate::theme::Background::Dark => &self.theme_dark,
+        };
+        variant.as_deref().unwrap_or(&self.theme)
+    }
+
+    fn apply(&mut self, p: PartialConfig) {
+        if let Some(v) = p.indent {
+            self.indent = v;
+        }
+        if let Some(v) = p.sort_keys {
+            self.sort_keys = v;
+        }
+        if let Some(v) = p.compact {
+            self.compact = v;
+        }
+        if let Some(v) = p.max_depth {
+            self.max_depth = if v == 0 { None } else { Some(v) };
+        }
+        if let Some(v) = p.max_string_length {
+            self.max_string_length = if v == 0 { None } else { Some(v) };
+        }
+        if let Some(v) = p.max_items {
+            self.max_items = if v == 0 { None } else { Some(v) };
+        }
+        if let Some(v) = p.get {
+            self.get = Some(v);
+        }
+        if let Some(v) = p.theme {
+            self.theme = v;
+        }
+        if let Some(v) = p.theme_light {
+            self.theme_light = Some(v);
+        }
+        if let Some(v) = p.theme_dark {
+            self.theme_dark = Some(v);
+        }
+        if let Some(v) = p.background {
+            self.background = Some(v);
+        }
+        if let Some(v) = p.raw {
+            self.raw = v;
+        }
+        if let Some(v) = p.format {
+            self.format = v;
+        }
+        if let Some(v) = p.input_format {
+            self.input_format = v;
+        }
+        if let Some(v) = p.color {
+            self.color = Some(v);
+        }
+        if let Some(v) = p.schema {
+            self.schema = Some(v);
+        }
+        if let Some(v) = p.lenient {
+            self.lenient = v;
+        }
+        if let Some(v) = p.annotate {
+            self.annotate = v;
+        }
+        if let Some(v) = p.line_numbers {
+            self.line_numbers = v;
+        }
+        if let Some(v) = p.paging {
+            self.paging = Some(v);
+        }
+        if let Some(v) = p.line_paths {
+            self.line_paths = v;
+        }
+        if let Some(v) = p.width {
+            self.width = Some(v);
+        }
+        if let Some(v) = p.indent_style {
+            self.indent_style = v;
+        }
+        if let Some(v) = p.newline {
+            self.newline = v;
+        }
+        if let Some(v) = p.final_newline {
+            self.final_newline = v;
+        }
+        if let Some(v) = p.key_separator {
+            self.key_separator = v;
+        }
+        if let Some(v) = p.value_separator {
+            self.value_separator = v;
+        }
+        if let Some(v) = p.columns {
+            self.columns = Some(v);
+        }
+        if let Some(v) = p.delimiter {
+            self.delimiter = v;
+        }
+        if let Some(v) = p.quote {
+            self.quote = v;
+        }
+        if let Some(v) = p.header {
+            self.header = v;
+        }
+        if let Some(v) = p.column_types {
+            self.column_types = Some(v);
+        }
+        if let Some(v) = p.themes {
+            merge_themes(&mut self.themes, v);
+        }
+    }
+
+    fn to_partial(&self) -> PartialConfig {
+        PartialConfig {
+            indent: Some(self.indent),
+            sort_keys: Some(self.sort_keys),
+            compact: Some(self.compact),
+            max_depth: self.max_depth,
+            max_string_length: self.max_string_length,
+            max_items: self.max_items,
+            get: self.get.clone(),
+            theme: Some(self.theme.clone()),
+            theme_light: self.theme_light.clone(),
+            theme_dark: self.theme_dark.clone(),
+            background: self.background,
+            raw: Some(self.raw),
+            format: Some(self.format.clone()),
+            input_format: Some(self.input_format.clone()),
+            color: self.color.clone(),
+            schema: self.schema.clone(),
+            lenient: Some(self.lenient),
+            annotate: Some(self.annotate),
+            line_numbers: Some(self.line_numbers),
+            paging: self.paging,
+            line_paths: Some(self.line_paths),
+            width: self.width,
+            indent_style: Some(self.indent_style),
+            newline: Some(self.newline),
+            final_newline: Some(self.final_newline),
+            key_separator: Some(self.key_separator.clone()),
+            value_separator: Some(self.value_separator.clone()),
+            columns: self.columns.clone(),
+            delimiter: Some(self.delimiter.clone()),
+            quote: Some(self.quote.clone()),
+            header: Some(self.header),
+            column_types: self.column_types.clone(),
+            themes: None,
+        }
+    }
 }
 
+/// Merge every config layer (system, user, project, environment) into one.
 pub fn load_config() -> Result<Option<PartialConfig>, JsonfizzError> {
-    let paths = get_config_paths();
-    for path in paths.iter() {
-        if path.exists() {
-            let content = fs::read_to_string(path)
-                .map_err(|e| JsonfizzError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
-            let partial: PartialConfig = toml::from_str(&content)
-                .map_err(|e| JsonfizzError::Config(format!("Failed to parse {}: {}", path.display(), e)))?;
-            return Ok(Some(partial));
+    load_config_for(None)
+}
+
+/// Like [`load_config`], with the `.editorconfig` settings for `file`, if
+/// given, as the lowest layer.
+pub fn load_config_for(file: Option<&Path>) -> Result<Option<PartialConfig>, JsonfizzError> {
+    let layers = load_layers_for(file)?;
+    if layers.is_empty() {
+        return Ok(None);
+    }
+    let mut merged = PartialConfig::default();
+    for layer in layers {
+        merged.overlay(layer.partial);
+    }
+    Ok(Some(merged))
+}
+
+/// Add `themes` from a higher layer to `into`, merging a theme defined in
+/// both field by field.
+fn merge_themes(into: &mut BTreeMap<String, crate::theme::ThemeDef>, themes: BTreeMap<String, crate::theme::ThemeDef>) {
+    for (name, def) in themes {
+        let merged = match into.remove(&name) {
+            Some(lower) => def.layered_over(lower),
+            None => def,
+        };
+        into.insert(name, merged);
+    }
+}
+
+/// Load the config layers that exist, lowest precedence first.
+pub fn load_layers() -> Result<Vec<ConfigLayer>, JsonfizzError> {
+    load_layers_for(None)
+}
+
+/// Like [`load_layers`], starting with the `.editorconfig` settings for
+/// `file` when it has any. jsonfizz's own config files override them.
+pub fn load_layers_for(file: Option<&Path>) -> Result<Vec<ConfigLayer>, JsonfizzError> {
+    let mut layers = Vec::new();
+    if let Some(file) = file {
+        let partial = crate::editorconfig::settings_for(file)?;
+        if partial != PartialConfig::default() {
+            layers.push(ConfigLayer { source: ConfigSource::EditorConfig(file.to_path_buf()), partial });
+        }
+    }
+    let mut seen = Vec::new();
+    let mut add_file = |path: PathBuf, source: fn(PathBuf) -> ConfigSource, layers: &mut Vec<ConfigLayer>| -> Result<(), JsonfizzError> {
+        if !path.is_file() || seen.contains(&path) {
+            return Ok(());
         }
+        let partial = read_config_file(&path)?;
+        seen.push(path.clone());
+        layers.push(ConfigLayer { source: source(path), partial });
+        Ok(())
+    };
+
+    if let Some(path) = system_config_path() {
+        add_file(path, ConfigSource::System, &mut layers)?;
+    }
+    for path in user_config_paths() {
+        add_file(path, ConfigSource::User, &mut layers)?;
+    }
+    if let Some(path) = std::env::current_dir().ok().and_then(|dir| find_project_config(&dir)) {
+        add_file(path, ConfigSource::Project, &mut layers)?;
+    }
+    layers.extend(env_layers(std::env::vars())?);
+    Ok(layers)
+}
+
+fn read_config_file(path: &Path) -> Result<PartialConfig, JsonfizzError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| JsonfizzError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+    toml::from_str(&content)
+        .map_err(|e| JsonfizzError::Config(format!("Failed to parse {}: {}", path.display(), e)))
+}
+
+/// Find the nearest `.jsonfizz.toml` in `start` or one of its ancestors.
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_NAME))
+        .find(|path| path.is_file())
+}
+
+/// Build one layer per `JSONFIZZ_*` variable that names a setting.
+///
+/// Integers and booleans are recognised where the setting expects them;
+/// anything else is taken as a string.
+pub fn env_layers<I: IntoIterator<Item = (String, String)>>(vars: I) -> Result<Vec<ConfigLayer>, JsonfizzError> {
+    let mut found: Vec<(usize, String, String)> = Vec::new();
+    for (name, raw) in vars {
+        let key = match name.strip_prefix("JSONFIZZ_") {
+            Some(key) => key.to_lowercase(),
+            None => continue,
+        };
+        if let Some(index) = FIELDS.iter().position(|f| *f == key) {
+            found.push((index, name, raw));
+        }
+    }
+    found.sort();
+
+    let mut layers = Vec::new();
+    for (index, name, raw) in found {
+        let mut candidates = Vec::new();
+        if let Ok(n) = raw.parse::<i64>() {
+            candidates.push(toml::Value::Integer(n));
+        } else if let Ok(b) = raw.parse::<bool>() {
+            candidates.push(toml::Value::Boolean(b));
+        }
+        candidates.push(toml::Value::String(raw.clone()));
+
+        let mut result = Err(String::new());
+        for value in candidates {
+            let mut table = toml::value::Table::new();
+            table.insert(FIELDS[index].to_string(), value);
+            result = toml::Value::Table(table).try_into::<PartialConfig>().map_err(|e| e.to_string());
+            if result.is_ok() {
+                break;
+            }
+        }
+        let partial = result
+            .map_err(|e| JsonfizzError::Config(format!("Invalid value '{}' in {}: {}", raw, name, e)))?;
+        layers.push(ConfigLayer { source: ConfigSource::Env(name), partial });
+    }
+    Ok(layers)
+}
+
+/// Render each effective setting with the layer that provided it.
+pub fn describe(cli: &crate::cli::CliArgs, layers: &[ConfigLayer]) -> String {
+    let mut tables = vec![(Config::merge(cli, None).to_partial().to_table(), ConfigSource::Default)];
+    tables.extend(layers.iter().map(|layer| (layer.partial.to_table(), layer.source.clone())));
+    tables.push((cli.explicit_partial().to_table(), ConfigSource::CommandLine));
+
+    // The light and dark variants only count from the layer that set `theme`.
+    let theme_layer = tables.iter().rposition(|(table, _)| table.contains_key("theme")).unwrap_or(0);
+    let mut out = String::new();
+    for field in FIELDS {
+        let from = if matches!(*field, "theme_light" | "theme_dark") { theme_layer } else { 0 };
+        let (value, source) = tables[from..].iter().rev()
+            .find(|(table, _)| table.contains_key(*field))
+            .map(|(table, source)| (table.get(*field), source))
+            .unwrap_or((None, &ConfigSource::Default));
+        let line = match value {
+            Some(v) => format!("{} = {}", field, v),
+            None => format!("# {} is not set", field),
+        };
+        out.push_str(&format!("{:<40} # {}\n", line, source));
+    }
+    out
+}
+
+fn system_config_path() -> Option<PathBuf> {
+    if cfg!(unix) {
+        Some(PathBuf::from("/etc/jsonfizz/config.toml"))
+    } else {
+        None
     }
-    Ok(None)
 }
 
-fn get_config_paths() -> Vec<PathBuf> {
+fn user_config_paths() -> Vec<PathBuf> {
     let mut paths = Vec::new();
     if let Some(config_dir) = dirs::config_dir() {
         paths.push(config_dir.join("jsonfizz").join("config.toml"));
     }
     if let Some(home_dir) = dirs::home_dir() {
         paths.push(home_dir.join(".config").join("jsonfizz").join("config.toml"));
-        paths.push(home_dir.join(".jsonfizz.toml"));
+        paths.push(home_dir.join(PROJECT_CONFIG_NAME));
     }
     paths
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_every_field_settable_in_toml() {
+        let partial: PartialConfig = toml::from_str(r#"
+            indent = 4
+            sort_keys = false
+            compact = true
+            max_depth = 3
+            max_string_length = 80
+            max_items = 5
+            get = "data.items"
+            theme = "ocean"
+            theme_light = "solarized"
+            theme_dark = "forest"
+            background = "light"
+            raw = true
+            format = "yaml"
+            input_format = "toml"
+            color = "never"
+            schema = "schema.json"
+            lenient = true
+            annotate = true
+            line_numbers = true
+            paging = "never"
+            line_paths = true
+            width = 100
+            indent_style = "tab"
+            newline = "crlf"
+            final_newline = false
+            key_separator = "__"
+            value_separator = ": "
+            columns = "id,user.name"
+            delimiter = "tab"
+            quote = "'"
+            header = false
+            column_types = "zip=string"
+        "#).unwrap();
+        assert_eq!(partial.to_table().len(), FIELDS.len());
+
+        let cli = crate::cli::CliArgs::parse_from(["jsonfizz"]);
+        let config = Config::merge(&cli, Some(partial));
+        assert_eq!(config.indent, 4);
+        assert!(config.compact);
+        assert_eq!(config.get.as_deref(), Some("data.items"));
+        assert!(config.raw);
+        assert_eq!(config.input_format, "toml");
+        assert!(matches!(config.color, Some(crate::cli::ColorChoice::Never)));
+        assert!(config.lenient);
+        assert_eq!(config.background, Some(crate::cli::BackgroundChoice::Light));
+        assert_eq!(config.theme_for(crate::theme::Background::Light), "solarized");
+        assert_eq!(config.theme_for(crate::theme::Background::Dark), "forest");
+    }
+
+    #[test]
+    fn test_theme_pair_yields_to_explicit_theme() {
+        let partial: PartialConfig = toml::from_str("theme_light = \"solarized\"\n").unwrap();
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz"]).unwrap();
+        let config = Config::merge(&cli, Some(partial.clone()));
+        assert_eq!(config.theme_for(crate::theme::Background::Light), "solarized");
+        assert_eq!(config.theme_for(crate::theme::Background::Dark), "default");
+
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz", "--theme", "ocean"]).unwrap();
+        let config = Config::merge(&cli, Some(partial));
+        assert_eq!(config.theme_for(crate::theme::Background::Light), "ocean");
+    }
+
+    #[test]
+    fn test_theme_pair_yields_to_theme_from_higher_layer() {
+        let user: PartialConfig = toml::from_str("theme_dark = \"ocean\"\n").unwrap();
+        let mut layers = vec![ConfigLayer { source: ConfigSource::User(PathBuf::from("config.toml")), partial: user }];
+        layers.extend(env_layers(vec![("JSONFIZZ_THEME".to_string(), "mono".to_string())]).unwrap());
+        let mut merged = PartialConfig::default();
+        for layer in layers.clone() {
+            merged.overlay(layer.partial);
+        }
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz"]).unwrap();
+        let config = Config::merge(&cli, Some(merged));
+        assert_eq!(config.theme_for(crate::theme::Background::Dark), "mono");
+        assert!(describe(&cli, &layers).contains("# theme_dark is not set"));
+
+        // A pair set in the same layer as the theme, or above it, still applies.
+        let mut merged: PartialConfig = toml::from_str("theme = \"mono\"\n").unwrap();
+        merged.overlay(toml::from_str("theme = \"forest\"\ntheme_dark = \"ocean\"\n").unwrap());
+        let config = Config::merge(&cli, Some(merged));
+        assert_eq!(config.theme_for(crate::theme::Background::Dark), "ocean");
+        assert_eq!(config.theme_for(crate::theme::Background::Light), "forest");
+    }
+
+    #[test]
+    fn test_global_flags_after_subcommand_are_explicit() {
+        let partial: PartialConfig = toml::from_str("theme = \"forest\"\ninput_format = \"toml\"\n").unwrap();
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz", "stats", "data.yaml", "--theme", "ocean", "--input-format", "yaml"]).unwrap();
+        assert!(matches!(&cli.command, Some(crate::cli::Command::Stats(stats)) if stats.files == ["data.yaml"]));
+        let config = Config::merge(&cli, Some(partial));
+        assert_eq!(config.theme, "ocean");
+        assert_eq!(config.input_format, "yaml");
+    }
+
+    #[test]
+    fn test_editorconfig_is_lowest_layer() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".editorconfig"), "root = true\n[*.json]\nindent_style = tab\nindent_size = 4\nend_of_line = crlf\n").unwrap();
+        let file = dir.path().join("data.json");
+        let layers = load_layers_for(Some(&file)).unwrap();
+        assert_eq!(layers[0].source, ConfigSource::EditorConfig(file.clone()));
+
+        // config.toml overrides .editorconfig, and command-line flags override both.
+        let mut merged = layers[0].partial.clone();
+        merged.overlay(toml::from_str("indent_style = \"space\"\n").unwrap());
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz", "--newline", "lf"]).unwrap();
+        let config = Config::merge(&cli, Some(merged));
+        assert_eq!(config.indent, 4);
+        assert_eq!(config.indent_style, crate::cli::IndentStyle::Space);
+        assert_eq!(config.newline, crate::cli::NewlineStyle::Lf);
+
+        let described = describe(&cli, &layers);
+        assert!(described.contains(&format!("# .editorconfig for {}", file.display())), "{}", described);
+    }
+
+    #[test]
+    fn test_inline_themes_merge_across_layers() {
+        let mut merged: PartialConfig = toml::from_str("[themes.night]\ninherits = \"ocean\"\n[themes.night.key]\nfg = \"cyan\"\n[themes.night.light.null]\nfg = \"red\"\n[themes.day.key]\nfg = \"blue\"\n").unwrap();
+        merged.overlay(toml::from_str("[themes.night.key]\nbold = true\n[themes.night.light.key]\nfg = \"black\"\n").unwrap());
+        let themes = merged.themes.unwrap();
+        assert_eq!(themes.len(), 2);
+        assert_eq!(themes["night"].inherits.as_deref(), Some("ocean"));
+        let key = themes["night"].key.as_ref().unwrap();
+        assert_eq!((key.fg.as_deref(), key.bold), (Some("cyan"), Some(true)));
+        let light = themes["night"].light.as_ref().unwrap();
+        assert!(light.key.is_some() && light.null.is_some());
+        assert_eq!(themes["day"].key.as_ref().unwrap().fg.as_deref(), Some("blue"));
+
+        let err = toml::from_str::<PartialConfig>("[themes.night.key]\nfg = 3\n").unwrap_err();
+        assert!(err.to_string().contains("themes.night.key") || err.to_string().contains("fg"), "{}", err);
+    }
+
+    #[test]
+    fn test_find_project_config_walks_up() {
+        let root = TempDir::new().unwrap();
+        let nested = root.path().join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_config(&nested), None);
+
+        let config_path = root.path().join("a").join(PROJECT_CONFIG_NAME);
+        fs::write(&config_path, "indent = 8\n").unwrap();
+        assert_eq!(find_project_config(&nested), Some(config_path.clone()));
+        assert_eq!(read_config_file(&config_path).unwrap().indent, Some(8));
+    }
+
+    #[test]
+    fn test_env_layers() {
+        let vars = vec![
+            ("JSONFIZZ_THEME".to_string(), "forest".to_string()),
+            ("JSONFIZZ_INDENT".to_string(), "3".to_string()),
+            ("JSONFIZZ_GET".to_string(), "42".to_string()),
+            ("JSONFIZZ_UNKNOWN".to_string(), "x".to_string()),
+            ("PATH".to_string(), "/bin".to_string()),
+        ];
+        let layers = env_layers(vars).unwrap();
+        assert_eq!(layers.len(), 3);
+        assert_eq!(layers[0].source, ConfigSource::Env("JSONFIZZ_INDENT".to_string()));
+        assert_eq!(layers[0].partial.indent, Some(3));
+        assert_eq!(layers[1].partial.get.as_deref(), Some("42"));
+        assert_eq!(layers[2].partial.theme.as_deref(), Some("forest"));
+
+        let err = env_layers(vec![("JSONFIZZ_INDENT".to_string(), "wide".to_string())]).unwrap_err();
+        assert!(err.to_string().contains("JSONFIZZ_INDENT"));
+    }
+
+    #[test]
+    fn test_overlay_later_layer_wins() {
+        let mut merged = PartialConfig {
+            indent: Some(2),
+            theme: Some("ocean".to_string()),
+            ..Default::default()
+        };
+        merged.overlay(PartialConfig {
+            indent: Some(4),
+            ..Default::default()
+        });
+        assert_eq!(merged.indent, Some(4));
+        assert_eq!(merged.theme.as_deref(), Some("ocean"));
+    }
+
+    #[test]
+    fn test_describe_reports_sources() {
+        let cli = crate::cli::CliArgs::parse_from(["jsonfizz"]);
+        let project = PathBuf::from("/repo/.jsonfizz.toml");
+        let layers = vec![
+            ConfigLayer {
+                source: ConfigSource::Project(project),
+                partial: PartialConfig { indent: Some(4), ..Default::default() },
+            },
+            ConfigLayer {
+                source: ConfigSource::Env("JSONFIZZ_THEME".to_string()),
+                partial: PartialConfig { theme: Some("forest".to_string()), ..Default::default() },
+            },
+        ];
+        let out = describe(&cli, &layers);
+        let line = |key: &str| out.lines().find(|l| l.starts_with(key)).unwrap().to_string();
+        assert!(line("indent = 4").ends_with("# project config /repo/.jsonfizz.toml"));
+        assert!(line("theme = \"forest\"").ends_with("# environment JSONFIZZ_THEME"));
+        assert!(line("format = \"json\"").ends_with("# default"));
+        assert!(line("# max_depth is not set").ends_with("# default"));
+
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz", "--indent", "6"]).unwrap();
+        let out = describe(&cli, &layers);
+        assert!(out.lines().any(|l| l.starts_with("indent = 6") && l.ends_with("# command line")));
+    }
+
+    fn project_and_env_layers() -> Vec<ConfigLayer> {
+        let mut layers = vec![ConfigLayer {
+            source: ConfigSource::Project(PathBuf::from("/repo/.jsonfizz.toml")),
+            partial: toml::from_str("indent = 8\ntheme = \"ocean\"\ncompact = false\nformat = \"yaml\"\n").unwrap(),
+        }];
+        layers.extend(env_layers(vec![("JSONFIZZ_INDENT".to_string(), "6".to_string())]).unwrap());
+        layers
+    }
+
+    fn merged(layers: Vec<ConfigLayer>) -> Option<PartialConfig> {
+        let mut merged = PartialConfig::default();
+        for layer in layers {
+            merged.overlay(layer.partial);
+        }
+        Some(merged)
+    }
+
+    #[test]
+    fn test_config_overrides_cli_defaults() {
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz"]).unwrap();
+        let config = Config::merge(&cli, merged(project_and_env_layers()));
+        assert_eq!(config.indent, 6);
+        assert_eq!(config.theme, "ocean");
+        assert_eq!(config.format, "yaml");
+    }
+
+    #[test]
+    fn test_explicit_cli_flags_override_config_and_env() {
+        let cli = crate::cli::CliArgs::try_parse_tracked_from([
+            "jsonfizz", "--indent", "4", "--theme", "forest", "--compact", "--format", "json",
+        ]).unwrap();
+        let config = Config::merge(&cli, merged(project_and_env_layers()));
+        assert_eq!(config.indent, 4);
+        assert_eq!(config.theme, "forest");
+        assert!(config.compact);
+        assert_eq!(config.format, "json");
+    }
+
+    #[test]
+    fn test_cli_false_overrides_config_true() {
+        let partial: PartialConfig = toml::from_str(
+            "compact = true\nraw = true\nlenient = true\nannotate = true\nline_numbers = true\nline_paths = true\nsort_keys = false\nfinal_newline = false\nheader = false\n",
+        ).unwrap();
+        let cli = crate::cli::CliArgs::try_parse_tracked_from([
+            "jsonfizz", "--compact=false", "--raw=false", "--lenient=false", "--annotate=false", "--line-numbers=false",
+            "--line-paths=false", "--sort-keys", "--no-final-newline=false", "--no-header=false", "data.json",
+        ]).unwrap();
+        assert_eq!(cli.files, ["data.json"]);
+        let config = Config::merge(&cli, Some(partial.clone()));
+        assert!(!config.compact && !config.raw && !config.lenient && !config.annotate);
+        assert!(!config.line_numbers && !config.line_paths);
+        assert!(config.sort_keys && config.final_newline && config.header);
+
+        // Without a value the flags still switch the setting on, and do not
+        // take the next argument as their value.
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz", "-c", "--raw", "data.json"]).unwrap();
+        assert_eq!(cli.files, ["data.json"]);
+        let config = Config::merge(&cli, Some(toml::from_str("compact = false\n").unwrap()));
+        assert!(config.compact && config.raw);
+        let config = Config::merge(&crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz"]).unwrap(), Some(partial));
+        assert!(config.compact && !config.sort_keys && !config.header);
+    }
+
+    #[test]
+    fn test_env_overrides_config_file() {
+        let cli = crate::cli::CliArgs::try_parse_tracked_from(["jsonfizz", "--theme", "forest"]).unwrap();
+        let config = Config::merge(&cli, merged(project_and_env_layers()));
+        assert_eq!(config.indent, 6);
+        assert_eq!(config.theme, "forest");
+    }
+}
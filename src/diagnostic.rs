@@ -0,0 +1,133 @@
+use owo_colors::OwoColorize;
+use crate::theme::Theme;
+
+const CONTEXT_BEFORE: usize = 2;
+const CONTEXT_AFTER: usize = 1;
+const MAX_LINE_WIDTH: usize = 120;
+
+/// Render the lines around a parse error with a caret under the offending column.
+///
+/// `line` and `column` are 1-based. Very long lines (minified input) are
+/// windowed around the column so the caret stays on screen.
+pub fn render_snippet(source: &str, name: &str, line: usize, column: Option<usize>, hint: Option<&str>, theme: &Theme) -> String {
+    let lines: Vec<&str> = source.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)).collect();
+    let line = line.clamp(1, lines.len());
+    let first = line.saturating_sub(CONTEXT_BEFORE).max(1);
+    let last = (line + CONTEXT_AFTER).min(lines.len());
+    let width = last.to_string().len();
+    let col_index = column.unwrap_or(1).saturating_sub(1);
+    let start = col_index.saturating_sub(MAX_LINE_WIDTH / 2);
+
+    let gutter = |label: &str| format!("{:>width$} |", label, width = width).style(theme.null).to_string();
+    let location = match column {
+        Some(c) => format!("{}:{}:{}", name, line, c),
+        None => format!("{}:{}", name, line),
+    };
+
+    let mut out = String::new();
+    out.push_str(&format!("\n{}{} {}", " ".repeat(width), "-->".style(theme.null), location));
+    out.push_str(&format!("\n{}", gutter("")));
+    for n in first..=last {
+        let text = window(lines[n - 1], start);
+        let number = if n == line {
+            format!("{:>width$} |", n, width = width).style(theme.error).to_string()
+        } else {
+            gutter(&n.to_string())
+        };
+        out.push_str(&format!("\n{} {}", number, text));
+        if n == line && column.is_some() {
+            let caret_at = col_index - start + usize::from(start > 0);
+            let padding: String = text
+                .chars()
+                .chain(std::iter::repeat(' '))
+                .take(caret_at)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            out.push_str(&format!("\n{} {}{}", gutter(""), padding, "^".style(theme.error)));
+        }
+    }
+    if let Some(hint) = hint {
+        out.push_str(&format!("\n{} {}", format!("{:>width$} =", "", width = width).style(theme.null), format!("hint: {}", hint).style(theme.error)));
+    }
+    out
+}
+
+fn window(text: &str, start: usize) -> String {
+    let count = text.chars().count();
+    if count <= MAX_LINE_WIDTH && start == 0 {
+        return text.to_string();
+    }
+    let body: String = text.chars().skip(start).take(MAX_LINE_WIDTH).collect();
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if start + MAX_LINE_WIDTH < count { "…" } else { "" };
+    format!("{}{}{}", prefix, body, suffix)
+}
+
+/// Guess the cause of a JSON parse error from the text around it.
+pub fn hint(source: &str, line: usize, column: usize, message: &str) -> Option<&'static str> {
+    let line_start: usize = source.split('\n').take(line.saturating_sub(1)).map(|l| l.len() + 1).sum();
+    let line_text = source.get(line_start..)?.split('\n').next()?;
+    let offset = line_start + line_text.char_indices().nth(column.saturating_sub(1)).map(|(i, _)| i).unwrap_or(line_text.len());
+    let current = source[offset..].chars().next();
+    let previous = source[..offset].chars().rev().find(|c| !c.is_whitespace());
+
+    if message.contains("trailing comma") || (matches!(current, Some('}') | Some(']')) && previous == Some(',')) {
+        return Some("remove the trailing comma before the closing bracket");
+    }
+    if current == Some('\'') {
+        return Some("JSON strings and keys must use double quotes, not single quotes");
+    }
+    if message.contains("key must be a string") && current.is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$') {
+        return Some("object keys must be wrapped in double quotes");
+    }
+    if message.contains("expected `,`") {
+        return Some("a comma may be missing between the previous element and this one");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_hint(input: &str) -> Option<&'static str> {
+        let err = serde_json::from_str::<serde_json::Value>(input).unwrap_err();
+        hint(input, err.line(), err.column(), &err.to_string())
+    }
+
+    #[test]
+    fn test_hints() {
+        assert!(parse_hint("{\"a\": 1,}").unwrap().contains("trailing comma"));
+        assert!(parse_hint("[1, 2,\n]").unwrap().contains("trailing comma"));
+        assert!(parse_hint("{\"a\": 'x'}").unwrap().contains("double quotes"));
+        assert!(parse_hint("{name: 1}").unwrap().contains("keys"));
+        assert!(parse_hint("{\"a\": 1\n \"b\": 2}").unwrap().contains("comma may be missing"));
+        assert_eq!(parse_hint("{\"a\": tru}"), None);
+    }
+
+    #[test]
+    fn test_render_snippet_caret() {
+        let theme = Theme::new("mono", true).unwrap();
+        let source = "{\n  \"a\": 1,\n  b: 2\n}\n";
+        let out = render_snippet(source, "data.json", 3, Some(3), Some("object keys must be wrapped in double quotes"), &theme);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[1], " --> data.json:3:3");
+        assert_eq!(lines[3], "1 | {");
+        assert_eq!(lines[5], "3 |   b: 2");
+        assert_eq!(lines[6], "  |   ^");
+        assert_eq!(lines[7], "4 | }");
+        assert_eq!(lines[8], "  = hint: object keys must be wrapped in double quotes");
+    }
+
+    #[test]
+    fn test_render_snippet_long_line() {
+        let theme = Theme::new("mono", true).unwrap();
+        let source = format!("[{}x]", "1,".repeat(500));
+        let out = render_snippet(&source, "<stdin>", 1, Some(1002), None, &theme);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[3].starts_with("1 | …"));
+        assert!(lines[3].chars().count() < MAX_LINE_WIDTH + 10);
+        let caret = lines[4].find('^').unwrap();
+        assert_eq!(lines[3].chars().nth(caret), Some('x'));
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+use ec4rs::property::{EndOfLine, FinalNewline, IndentSize, IndentStyle, MaxLineLen, TabWidth};
+use crate::cli::{self, NewlineStyle};
+use crate::config::PartialConfig;
+use crate::error::JsonfizzError;
+
+/// The `.editorconfig` settings that apply to `file`, read from its
+/// directory upward and matched against its name, as a config layer.
+///
+/// `indent_style`, `indent_size` (or `tab_width`), `end_of_line`,
+/// `insert_final_newline` and `max_line_length` are used; other properties
+/// and values jsonfizz has no equivalent for (`end_of_line = cr`,
+/// `max_line_length = off`) are ignored.
+pub fn settings_for(file: &Path) -> Result<PartialConfig, JsonfizzError> {
+    let mut properties = ec4rs::properties_of(file)
+        .map_err(|e| JsonfizzError::Config(format!("Failed to read .editorconfig for {}: {}", file.display(), e)))?;
+    properties.use_fallbacks();
+
+    let indent = match properties.get::<IndentSize>() {
+        Ok(IndentSize::Value(size)) => Some(size),
+        Ok(IndentSize::UseTabWidth) => match properties.get::<TabWidth>() {
+            Ok(TabWidth::Value(width)) => Some(width),
+            _ => None,
+        },
+        Err(_) => None,
+    };
+    Ok(PartialConfig {
+        indent,
+        indent_style: properties.get::<IndentStyle>().ok().map(|style| match style {
+            IndentStyle::Tabs => cli::IndentStyle::Tab,
+            IndentStyle::Spaces => cli::IndentStyle::Space,
+        }),
+        newline: match properties.get::<EndOfLine>() {
+            Ok(EndOfLine::Lf) => Some(NewlineStyle::Lf),
+            Ok(EndOfLine::CrLf) => Some(NewlineStyle::Crlf),
+            _ => None,
+        },
+        final_newline: match properties.get::<FinalNewline>() {
+            Ok(FinalNewline::Value(insert)) => Some(insert),
+            Err(_) => None,
+        },
+        width: match properties.get::<MaxLineLen>() {
+            Ok(MaxLineLen::Value(width)) => Some(width),
+            _ => None,
+        },
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_for_matching_sections() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(".editorconfig"), "root = true\n\n[*]\nend_of_line = crlf\ninsert_final_newline = false\n\n[*.json]\nindent_style = tab\ntab_width = 8\nmax_line_length = 100\n\n[*.yaml]\nindent_style = space\nindent_size = 4\nmax_line_length = off\n").unwrap();
+        let nested = dir.path().join("src");
+        std::fs::create_dir(&nested).unwrap();
+
+        let json = settings_for(&nested.join("data.json")).unwrap();
+        assert_eq!(json.indent_style, Some(cli::IndentStyle::Tab));
+        assert_eq!(json.indent, Some(8));
+        assert_eq!(json.newline, Some(NewlineStyle::Crlf));
+        assert_eq!(json.final_newline, Some(false));
+        assert_eq!(json.width, Some(100));
+
+        let yaml = settings_for(&nested.join("data.yaml")).unwrap();
+        assert_eq!(yaml.indent_style, Some(cli::IndentStyle::Space));
+        assert_eq!(yaml.indent, Some(4));
+        assert_eq!(yaml.width, None);
+
+        let other = settings_for(&nested.join("notes.txt")).unwrap();
+        assert_eq!(other.indent_style, None);
+        assert_eq!(other.newline, Some(NewlineStyle::Crlf));
+        assert_eq!(other.theme, None);
+    }
+}
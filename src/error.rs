@@ -2,11 +2,14 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum JsonfizzError {
-    #[error("{format} parse error{loc}: {message}")]
+    #[error("{format} parse error{}: {message}{}", location(.file, .line, .column), .snippet.as_deref().unwrap_or(""))]
     Parse {
         format: &'static str,
         message: String,
-        loc: String,
+        file: Option<String>,
+        line: Option<usize>,
+        column: Option<usize>,
+        snippet: Option<String>,
     },
 
     #[error("Path error: {0}")]
@@ -15,32 +18,162 @@ pub enum JsonfizzError {
     #[error("Config error: {0}")]
     Config(String),
 
+    #[error("{0}")]
+    Schema(String),
+
     #[error("Error: {0}")]
     Data(String),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// An IO error reading a named input, schema or other file.
+    #[error("IO error in {file}: {source}")]
+    FileIo {
+        file: String,
+        source: std::io::Error,
+    },
+}
+
+fn location(file: &Option<String>, line: &Option<usize>, column: &Option<usize>) -> String {
+    let file = file.as_ref().map(|f| format!(" in {}", f)).unwrap_or_default();
+    let pos = match (line, column) {
+        (Some(l), Some(c)) => format!(" at line {}, column {}", l, c),
+        (Some(l), None) => format!(" at line {}", l),
+        (None, Some(c)) => format!(" at column {}", c),
+        (None, None) => String::new(),
+    };
+    format!("{}{}", file, pos)
 }
 
 impl JsonfizzError {
     pub fn exit_code(&self) -> i32 {
         match self {
-            JsonfizzError::Parse { .. } | JsonfizzError::Path(_) => 1,
-            _ => 2,
+            JsonfizzError::Parse { .. } => 1,
+            JsonfizzError::Config(_) => 2,
+            JsonfizzError::Path(_) => 3,
+            JsonfizzError::Schema(_) => 4,
+            JsonfizzError::Io(_) | JsonfizzError::FileIo { .. } => 5,
+            JsonfizzError::Data(_) => 6,
         }
     }
 
-    pub fn parse_error(format: &'static str, message: impl Into<String>, line: Option<usize>, column: Option<usize>) -> Self {
-        let loc = match (line, column) {
-            (Some(l), Some(c)) => format!(" at line {}, column {}", l, c),
-            (Some(l), None) => format!(" at line {}", l),
-            (None, Some(c)) => format!(" at column {}", c),
-            (None, None) => String::new(),
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JsonfizzError::Parse { .. } => "parse",
+            JsonfizzError::Path(_) => "path",
+            JsonfizzError::Config(_) => "config",
+            JsonfizzError::Schema(_) => "schema",
+            JsonfizzError::Data(_) => "data",
+            JsonfizzError::Io(_) | JsonfizzError::FileIo { .. } => "io",
+        }
+    }
+
+    /// Structured form of the error for `--error-format json`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let (format, message, file, line, column) = match self {
+            JsonfizzError::Parse { format, message, file, line, column, .. } => (Some(*format), message.clone(), file.clone(), *line, *column),
+            JsonfizzError::Path(m) | JsonfizzError::Config(m) | JsonfizzError::Schema(m) | JsonfizzError::Data(m) => (None, m.clone(), None, None, None),
+            JsonfizzError::Io(e) => (None, e.to_string(), None, None, None),
+            JsonfizzError::FileIo { file, source } => (None, source.to_string(), Some(file.clone()), None, None),
         };
+        serde_json::json!({
+            "kind": self.kind(),
+            "format": format,
+            "message": message,
+            "file": file,
+            "line": line,
+            "column": column,
+            "exit_code": self.exit_code(),
+        })
+    }
+
+    /// Wrap an IO error from accessing `file` so that it names the file.
+    pub fn file_io(file: &str, source: std::io::Error) -> Self {
+        JsonfizzError::FileIo { file: file.to_string(), source }
+    }
+
+    pub fn parse_error(format: &'static str, message: impl Into<String>, line: Option<usize>, column: Option<usize>) -> Self {
         JsonfizzError::Parse {
             format,
             message: message.into(),
-            loc,
+            file: None,
+            line,
+            column,
+            snippet: None,
         }
     }
+
+    /// Attach the input name and a rendered source snippet to a parse error.
+    /// Other variants are returned unchanged.
+    pub fn with_source(self, name: &str, source: &str, theme: &crate::theme::Theme) -> Self {
+        match self {
+            JsonfizzError::Parse { format, message, line, column, .. } => {
+                let snippet = line.map(|l| {
+                    let hint = if format == "JSON" {
+                        crate::diagnostic::hint(source, l, column.unwrap_or(1), &message)
+                    } else {
+                        None
+                    };
+                    crate::diagnostic::render_snippet(source, name, l, column, hint, theme)
+                });
+                JsonfizzError::Parse {
+                    format,
+                    message,
+                    file: Some(name.to_string()),
+                    line,
+                    column,
+                    snippet,
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            JsonfizzError::parse_error("JSON", "bad", None, None),
+            JsonfizzError::Config("bad".to_string()),
+            JsonfizzError::Path("bad".to_string()),
+            JsonfizzError::Schema("bad".to_string()),
+            JsonfizzError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "bad")),
+            JsonfizzError::Data("bad".to_string()),
+        ];
+        let codes: Vec<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        assert_eq!(codes, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let theme = crate::theme::Theme::new("mono", true).unwrap();
+        let err = JsonfizzError::parse_error("JSON", "trailing comma", Some(2), Some(1)).with_source("data.json", "[1,\n]", &theme);
+        assert_eq!(err.to_json(), json!({
+            "kind": "parse",
+            "format": "JSON",
+            "message": "trailing comma",
+            "file": "data.json",
+            "line": 2,
+            "column": 1,
+            "exit_code": 1,
+        }));
+
+        let err = JsonfizzError::Path("Key 'x' not found".to_string());
+        assert_eq!(err.to_json()["kind"], "path");
+        assert_eq!(err.to_json()["file"], serde_json::Value::Null);
+        assert_eq!(err.to_json()["exit_code"], 3);
+
+        let err = JsonfizzError::file_io("missing.json", std::io::Error::new(std::io::ErrorKind::NotFound, "No such file"));
+        assert_eq!(err.to_string(), "IO error in missing.json: No such file");
+        assert_eq!(err.to_json()["kind"], "io");
+        assert_eq!(err.to_json()["file"], "missing.json");
+        assert_eq!(err.to_json()["message"], "No such file");
+        assert_eq!(err.exit_code(), 5);
+    }
 }
@@ -0,0 +1,236 @@
+use serde_json::{Map, Value};
+use crate::config::Config;
+use crate::error::JsonfizzError;
+use crate::formatter::{format_value, string_kind};
+use crate::path::{format_path_with, parse_path_with, JsonPath, PathSegment};
+use crate::theme::{colorize, Theme, TokenKind};
+
+/// Flatten `value` into a single-level object mapping the path of every
+/// scalar (in [`crate::path`] syntax, keys joined by `separator`) to its
+/// value. Empty objects and arrays are kept as values so that
+/// [`unflatten`] gives back the same document; an empty object at the root
+/// has no paths and flattens to an empty object. Fails when two values would
+/// get the same path, such as `{"a.b": 1, "a": {"b": 2}}`.
+pub fn flatten(value: &Value, separator: &str) -> Result<Map<String, Value>, JsonfizzError> {
+    check_separator(separator)?;
+    let mut out = Map::new();
+    collect(value, separator, true, &mut Vec::new(), &mut out)?;
+    Ok(out)
+}
+
+/// Like [`flatten`], but only objects are flattened; arrays are kept whole
+/// as values. Used for CSV columns.
+pub fn flatten_objects(value: &Value, separator: &str) -> Result<Map<String, Value>, JsonfizzError> {
+    check_separator(separator)?;
+    let mut out = Map::new();
+    collect(value, separator, false, &mut Vec::new(), &mut out)?;
+    Ok(out)
+}
+
+/// An empty `--key-separator` would make `ab` the path of `{"a": {"b": …}}`
+/// and split every key into single characters when unflattening.
+pub(crate) fn check_separator(separator: &str) -> Result<(), JsonfizzError> {
+    if separator.is_empty() {
+        return Err(JsonfizzError::Config("--key-separator cannot be empty".to_string()));
+    }
+    Ok(())
+}
+
+fn collect(value: &Value, separator: &str, arrays: bool, path: &mut JsonPath, out: &mut Map<String, Value>) -> Result<(), JsonfizzError> {
+    match value {
+        Value::Array(arr) if arrays && !arr.is_empty() => {
+            for (i, v) in arr.iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                collect(v, separator, arrays, path, out)?;
+                path.pop();
+            }
+        }
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                path.push(PathSegment::Key(k.clone()));
+                collect(v, separator, arrays, path, out)?;
+                path.pop();
+            }
+        }
+        Value::Object(_) if path.is_empty() => {}
+        leaf => {
+            let key = format_path_with(path, separator);
+            if out.contains_key(&key) {
+                return Err(JsonfizzError::Data(format!("Cannot flatten: '{}' is the path of more than one value; try another --key-separator", key)));
+            }
+            out.insert(key, leaf.clone());
+        }
+    }
+    Ok(())
+}
+
+/// How many more array slots than keys [`unflatten`] may create, for sparse
+/// indexes such as `a[5]`. Every slot of a flattened document has a key of
+/// its own, so this only bounds padding.
+pub(crate) const MAX_PADDING: usize = 1024;
+
+/// Rebuild the nested document from an object keyed by flattened paths.
+/// Index segments create arrays, padded with `null` up to the largest
+/// index. An array of such objects is unflattened item by item.
+pub fn unflatten(value: &Value, separator: &str) -> Result<Value, JsonfizzError> {
+    check_separator(separator)?;
+    match value {
+        Value::Object(map) => {
+            let mut root = Value::Null;
+            let mut slots = map.len() + MAX_PADDING;
+            for (key, v) in map {
+                let path = parse_path_with(key, separator)?;
+                insert(&mut root, &path, v.clone(), &mut slots).map_err(|e| match e {
+                    InsertError::Overlap => JsonfizzError::Data(format!("Cannot unflatten '{}': it overlaps another key", key)),
+                    InsertError::IndexTooLarge(i) => JsonfizzError::Data(format!("Cannot unflatten '{}': index {} is too large for {}", key, i, crate::formatter::counted(map.len(), "key"))),
+                })?;
+            }
+            Ok(if map.is_empty() { Value::Object(Map::new()) } else { root })
+        }
+        Value::Array(arr) => arr.iter().map(|item| unflatten(item, separator)).collect::<Result<_, _>>().map(Value::Array),
+        _ => Err(JsonfizzError::Data("--unflatten needs an object keyed by paths, or an array of them".to_string())),
+    }
+}
+
+/// Why [`insert`] could not place a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InsertError {
+    /// The path runs into or through a value placed by another path.
+    Overlap,
+    /// Padding the array up to this index would need more than the slots left.
+    IndexTooLarge(usize),
+}
+
+/// Place `value` at `path` under `node`, creating objects and arrays on the
+/// way. `slots` is how many array items may still be created, and is
+/// reduced by those that are.
+pub(crate) fn insert(node: &mut Value, path: &[PathSegment], value: Value, slots: &mut usize) -> Result<(), InsertError> {
+    let Some((first, rest)) = path.split_first() else {
+        if !node.is_null() {
+            return Err(InsertError::Overlap);
+        }
+        *node = value;
+        return Ok(());
+    };
+    match first {
+        PathSegment::Key(k) => {
+            if node.is_null() {
+                *node = Value::Object(Map::new());
+            }
+            let Value::Object(map) = node else {
+                return Err(InsertError::Overlap);
+            };
+            insert(map.entry(k.clone()).or_insert(Value::Null), rest, value, slots)
+        }
+        PathSegment::Index(i) => {
+            if node.is_null() {
+                *node = Value::Array(Vec::new());
+            }
+            let Value::Array(arr) = node else {
+                return Err(InsertError::Overlap);
+            };
+            if arr.len() <= *i {
+                let grow = i.checked_add(1)
+                    .map(|len| len - arr.len())
+                    .filter(|grow| grow <= slots)
+                    .ok_or(InsertError::IndexTooLarge(*i))?;
+                *slots -= grow;
+                arr.resize(arr.len() + grow, Value::Null);
+            }
+            insert(&mut arr[*i], rest, value, slots)
+        }
+    }
+}
+
+/// `--format lines`: one `path=value` line per scalar of the flattened
+/// document. Strings are written without quotes, everything else as JSON.
+/// Paths and strings holding control characters are quoted and escaped as
+/// JSON instead, so a newline cannot start a line of its own and escape
+/// sequences do not reach the terminal.
+pub fn to_lines(value: &Value, config: &Config, theme: &Theme) -> Result<String, JsonfizzError> {
+    let compact = Config { compact: true, ..Default::default() };
+    let separator = colorize(&config.value_separator, TokenKind::Punctuation, theme);
+    let mut lines = Vec::new();
+    for (path, v) in flatten(value, &config.key_separator)? {
+        let text = match &v {
+            Value::String(s) if !s.chars().any(char::is_control) => colorize(s, string_kind(s), theme),
+            other => format_value(other, &compact, theme, 0)?,
+        };
+        let path = if path.chars().any(char::is_control) {
+            format_value(&Value::String(path), &compact, theme, 0)?
+        } else {
+            colorize(&path, TokenKind::Key, theme)
+        };
+        lines.push(format!("{path}{separator}{text}"));
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_round_trip() {
+        let value = json!({"a": {"b": [1, {"c": null}]}, "e": {}, "s": "x"});
+        let flat = flatten(&value, ".").unwrap();
+        assert_eq!(Value::Object(flat.clone()), json!({"a.b[0]": 1, "a.b[1].c": null, "e": {}, "s": "x"}));
+        assert_eq!(unflatten(&Value::Object(flat), ".").unwrap(), value);
+
+        let env = flatten(&value, "__").unwrap();
+        assert!(env.contains_key("a__b[1]__c"));
+        assert_eq!(unflatten(&Value::Object(env), "__").unwrap(), value);
+
+        assert_eq!(Value::Object(flatten(&json!([[1], 2]), ".").unwrap()), json!({"[0][0]": 1, "[1]": 2}));
+        assert_eq!(unflatten(&json!({"[1]": "b", "[0]": "a"}), ".").unwrap(), json!(["a", "b"]));
+        assert_eq!(Value::Object(flatten(&json!(5), ".").unwrap()), json!({"": 5}));
+        assert_eq!(Value::Object(flatten_objects(&value, ".").unwrap()), json!({"a.b": [1, {"c": null}], "e": {}, "s": "x"}));
+    }
+
+    #[test]
+    fn test_flatten_empty_root_object() {
+        assert!(flatten(&json!({}), ".").unwrap().is_empty());
+        assert!(flatten_objects(&json!({}), ".").unwrap().is_empty());
+        assert_eq!(unflatten(&json!({}), ".").unwrap(), json!({}));
+        assert_eq!(unflatten(&json!([{}, {"a.b": 1}]), ".").unwrap(), json!([{}, {"a": {"b": 1}}]));
+    }
+
+    #[test]
+    fn test_flatten_collisions_and_separator() {
+        let value = json!({"a.b": 1, "a": {"b": 2}});
+        assert!(matches!(flatten(&value, "."), Err(JsonfizzError::Data(_))));
+        assert!(matches!(flatten_objects(&value, "."), Err(JsonfizzError::Data(_))));
+        assert_eq!(flatten(&value, "/").unwrap().len(), 2);
+
+        assert!(matches!(flatten(&json!({"a": 1}), ""), Err(JsonfizzError::Config(_))));
+        assert!(matches!(unflatten(&json!({"ab": 1}), ""), Err(JsonfizzError::Config(_))));
+    }
+
+    #[test]
+    fn test_unflatten_sparse_and_conflicts() {
+        assert_eq!(unflatten(&json!({"a[2]": true}), ".").unwrap(), json!({"a": [null, null, true]}));
+        assert_eq!(unflatten(&json!([{"x.y": 1}, {"x.y": 2}]), ".").unwrap(), json!([{"x": {"y": 1}}, {"x": {"y": 2}}]));
+        assert!(matches!(unflatten(&json!({"a": 1, "a.b": 2}), "."), Err(JsonfizzError::Data(_))));
+        assert!(matches!(unflatten(&json!({"a[0]": 1, "a.b": 2}), "."), Err(JsonfizzError::Data(_))));
+        assert!(matches!(unflatten(&json!({"a[x]": 1}), "."), Err(JsonfizzError::Path(_))));
+        assert!(unflatten(&json!("a"), ".").is_err());
+
+        for huge in [json!({"a[18446744073709551615]": 1}), json!({"a[4000000000]": 1})] {
+            let err = unflatten(&huge, ".").unwrap_err();
+            assert!(err.to_string().contains("is too large"), "{}", err);
+        }
+        assert_eq!(unflatten(&json!({"a[1024]": 1}), ".").unwrap()["a"].as_array().unwrap().len(), 1025);
+    }
+
+    #[test]
+    fn test_to_lines() {
+        let config = Config { key_separator: "_".to_string(), value_separator: ": ".to_string(), ..Default::default() };
+        let lines = to_lines(&json!({"db": {"host": "localhost", "ports": [5432, 5433]}, "debug": false}), &config, &Theme::plain()).unwrap();
+        assert_eq!(lines, "db_host: localhost\ndb_ports[0]: 5432\ndb_ports[1]: 5433\ndebug: false");
+
+        let hostile = json!({"k": "x\nEVIL=1", "t\u{1b}[2J": "\u{1b}]0;title\u{7}"});
+        let lines = to_lines(&hostile, &Config::default(), &Theme::plain()).unwrap();
+        assert_eq!(lines, "k=\"x\\nEVIL=1\"\n\"t\\u001b[2J\"=\"\\u001b]0;title\\u0007\"");
+    }
+}
@@ -1,75 +1,477 @@
 use serde_json::Value;
+use crate::cli::IndentStyle;
 use crate::config::Config;
 use crate::error::JsonfizzError;
+use crate::path::{format_path, JsonPath, PathSegment};
 use crate::theme::{colorize, TokenKind, Theme};
 
+/// Paths longer than this are cut from the left in the `--line-paths` gutter.
+const MAX_GUTTER_PATH: usize = 40;
+
 pub fn format_value(value: &Value, config: &Config, theme: &Theme, indent_level: usize) -> Result<String, JsonfizzError> {
-    let current_indent = " ".repeat(indent_level * config.indent);
-    let child_indent = " ".repeat((indent_level + 1) * config.indent);
+    format_marked(value, config, theme, indent_level, &[])
+}
 
+/// Like [`format_value`], but renders the nodes at `marks` (paths relative to
+/// `value`) in the theme's error style. Used to highlight repaired regions.
+pub fn format_marked(value: &Value, config: &Config, theme: &Theme, indent_level: usize, marks: &[JsonPath]) -> Result<String, JsonfizzError> {
+    let gutter = config.line_numbers || config.line_paths;
+    let mut formatter = Formatter { config, theme, marks, lines: (gutter && !config.compact).then(Vec::new) };
     if config.compact {
-        return Ok(serde_json::to_string(value).map_err(|e| JsonfizzError::parse_error("JSON", e.to_string(), None, None))?);
+        return Ok(formatter.flat(value, indent_level, &mut Vec::new()));
     }
+    let output = formatter.node(value, indent_level, &mut Vec::new(), indent_level * config.indent);
+    Ok(match formatter.lines {
+        Some(lines) => with_gutter(&output, &lines, config, theme),
+        None => output,
+    })
+}
 
-    if let Some(max_d) = config.max_depth {
-        if indent_level > max_d {
-            return Ok(match value {
-                Value::Object(_) => colorize("{…}", TokenKind::Punctuation, theme),
-                Value::Array(_) => colorize("[…]", TokenKind::Punctuation, theme),
-                _ => colorize("…", TokenKind::String, theme),
-            });
-        }
+/// Prefix each line with its number, or with the path of the node it shows
+/// when `line_paths` is set. `paths` has one entry per line of `output`.
+fn with_gutter(output: &str, paths: &[JsonPath], config: &Config, theme: &Theme) -> String {
+    debug_assert_eq!(output.lines().count(), paths.len());
+    let labels: Vec<String> = if config.line_paths {
+        paths.iter().map(gutter_path).collect()
+    } else {
+        (1..=paths.len()).map(|n| n.to_string()).collect()
+    };
+    let width = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0);
+    output
+        .lines()
+        .zip(labels)
+        .map(|(line, label)| {
+            let label = if config.line_paths { format!("{:<width$}", label) } else { format!("{:>width$}", label) };
+            format!("{} {}", colorize(&format!("{} │", label), TokenKind::Gutter, theme), line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn gutter_path(path: &JsonPath) -> String {
+    let text = format_path(path);
+    let len = text.chars().count();
+    if text.is_empty() {
+        "(root)".to_string()
+    } else if len > MAX_GUTTER_PATH {
+        format!("…{}", text.chars().skip(len + 1 - MAX_GUTTER_PATH).collect::<String>())
+    } else {
+        text
     }
+}
 
-    match value {
-        Value::Null => Ok(colorize("null", TokenKind::Null, theme)),
-        Value::Bool(b) => Ok(colorize(&b.to_string(), TokenKind::Bool, theme)),
-        Value::Number(n) => Ok(colorize(&n.to_string(), TokenKind::Number, theme)),
-        Value::String(s) => {
-            let display = if let Some(max_len) = config.max_string_length {
-                if s.chars().count() > max_len {
-                    let truncated: String = s.chars().take(max_len.saturating_sub(1)).collect();
-                    format!("{}…", truncated)
-                } else {
-                    s.clone()
+/// The settings for formatting one document, plus the per-line paths
+/// collected for the gutter.
+struct Formatter<'a> {
+    config: &'a Config,
+    theme: &'a Theme,
+    marks: &'a [JsonPath],
+    /// The path of the node each output line belongs to, in order; only
+    /// collected when a gutter needs it.
+    lines: Option<Vec<JsonPath>>,
+}
+
+impl Formatter<'_> {
+    /// Format the node at `path`, expanding objects and arrays over several
+    /// lines unless they fit in `--width`. `used` is the width its first line
+    /// already takes (indentation and key) plus the comma after it.
+    fn node(&mut self, value: &Value, indent_level: usize, path: &mut JsonPath, used: usize) -> String {
+        if let Some(lines) = &mut self.lines {
+            lines.push(path.clone());
+        }
+        let len = match value {
+            Value::Array(arr) => arr.len(),
+            Value::Object(map) => map.len(),
+            _ => 0,
+        };
+        if len == 0 || self.elided(indent_level) || self.fits_inline(value, indent_level, used) {
+            return self.flat(value, indent_level, path);
+        }
+
+        let theme = self.theme;
+        let current_indent = self.indentation(indent_level);
+        let child_indent = self.indentation(indent_level + 1);
+        let child_width = (indent_level + 1) * self.config.indent;
+        let mut items = Vec::with_capacity(len);
+        let (open, close) = match value {
+            Value::Array(arr) => {
+                let shown = self.shown_items(len);
+                for (i, v) in arr.iter().take(shown).enumerate() {
+                    let more = i + 1 < len;
+                    path.push(PathSegment::Index(i));
+                    let item = self.node(v, indent_level + 1, path, child_width + usize::from(more));
+                    path.pop();
+                    items.push(self.entry_line(format!("{child_indent}{item}"), None, v, more, indent_level));
+                }
+                if shown < len {
+                    if let Some(lines) = &mut self.lines {
+                        lines.push(path.clone());
+                    }
+                    items.push(format!("{child_indent}{}", colorize(&more_items(len - shown), TokenKind::Annotation, theme)));
                 }
-            } else {
-                s.clone()
+                ("[", "]")
+            }
+            Value::Object(map) => {
+                let colon = colorize(":", TokenKind::Colon, theme);
+                for (i, (k, v)) in self.entries(map).into_iter().enumerate() {
+                    let more = i + 1 < len;
+                    let key_str = format!("\"{}\"", highlight_escapes(k, TokenKind::Key, theme));
+                    let used = child_width + escape(k).chars().count() + 4 + usize::from(more);
+                    path.push(PathSegment::Key(k.clone()));
+                    let val = self.node(v, indent_level + 1, path, used);
+                    path.pop();
+                    items.push(self.entry_line(format!("{child_indent}{key_str}{colon} {val}"), Some(k), v, more, indent_level));
+                }
+                ("{", "}")
+            }
+            _ => unreachable!("scalars are formatted flat"),
+        };
+        if let Some(lines) = &mut self.lines {
+            lines.push(path.clone());
+        }
+        let open = colorize(open, TokenKind::Bracket(indent_level), theme);
+        let close = colorize(close, self.kind(path, TokenKind::Bracket(indent_level)), theme);
+        format!("{open}\n{}\n{current_indent}{close}", items.join("\n"))
+    }
+
+    /// Render a node on one line, as `[1, 2]` or `{"a": 1}`; without the
+    /// spaces in `--compact` mode.
+    fn flat(&self, value: &Value, indent_level: usize, path: &mut JsonPath) -> String {
+        let theme = self.theme;
+        let space = if self.config.compact { "" } else { " " };
+        if self.elided(indent_level) {
+            let (open, close) = match value {
+                Value::Object(_) => ("{", "}"),
+                Value::Array(_) => ("[", "]"),
+                _ => return colorize("…", TokenKind::String, theme),
             };
-            Ok(format!("\"{}\"", colorize(&display, TokenKind::String, theme)))
+            let summary = elided_summary(value).map(|s| colorize(&s, TokenKind::Annotation, theme)).unwrap_or_default();
+            return format!("{}{summary}{}", colorize(open, TokenKind::Punctuation, theme), colorize(close, TokenKind::Punctuation, theme));
         }
-        Value::Array(arr) => {
-            let mut items = Vec::new();
-            for (i, v) in arr.iter().enumerate() {
-                let item = format_value(v, config, theme, indent_level + 1)?;
-                items.push(if i + 1 < arr.len() {
-                    format!("{child_indent}{item},")
+        let marked = self.marks.iter().any(|m| m == path);
+        match value {
+            Value::Null => colorize("null", self.kind(path, TokenKind::Null), theme),
+            Value::Bool(b) => colorize(&b.to_string(), self.kind(path, TokenKind::Bool), theme),
+            Value::Number(n) => {
+                let number_kind = if n.is_f64() { TokenKind::Float } else { TokenKind::Integer };
+                colorize(&n.to_string(), self.kind(path, number_kind), theme)
+            }
+            Value::String(s) => {
+                let (display, summary) = self.truncate(s);
+                let summary = summary.unwrap_or_default();
+                // The summary goes after the closing quote so that it cannot
+                // be mistaken for the end of the string.
+                if marked {
+                    colorize(&format!("\"{}\"{}", escape(&display), summary), TokenKind::Error, theme)
                 } else {
-                    format!("{child_indent}{item}")
-                });
+                    let string_kind = string_kind(s);
+                    let summary = if summary.is_empty() { summary } else { colorize(&summary, TokenKind::Annotation, theme) };
+                    format!("\"{}\"{}", highlight_escapes(&display, string_kind, theme), summary)
+                }
+            }
+            Value::Array(arr) => {
+                let comma = colorize(",", TokenKind::Comma, theme);
+                let shown = self.shown_items(arr.len());
+                let mut items: Vec<String> = arr.iter().take(shown).enumerate().map(|(i, v)| {
+                    path.push(PathSegment::Index(i));
+                    let item = self.flat(v, indent_level + 1, path);
+                    path.pop();
+                    item
+                }).collect();
+                if shown < arr.len() {
+                    items.push(colorize(&more_items(arr.len() - shown), TokenKind::Annotation, theme));
+                }
+                let open = colorize("[", TokenKind::Bracket(indent_level), theme);
+                let close = colorize("]", self.kind(path, TokenKind::Bracket(indent_level)), theme);
+                format!("{open}{}{close}", items.join(&format!("{comma}{space}")))
+            }
+            Value::Object(map) => {
+                let comma = colorize(",", TokenKind::Comma, theme);
+                let colon = colorize(":", TokenKind::Colon, theme);
+                let items: Vec<String> = self.entries(map).into_iter().map(|(k, v)| {
+                    path.push(PathSegment::Key(k.clone()));
+                    let val = self.flat(v, indent_level + 1, path);
+                    path.pop();
+                    format!("\"{}\"{colon}{space}{val}", highlight_escapes(k, TokenKind::Key, theme))
+                }).collect();
+                let open = colorize("{", TokenKind::Bracket(indent_level), theme);
+                let close = colorize("}", self.kind(path, TokenKind::Bracket(indent_level)), theme);
+                format!("{open}{}{close}", items.join(&format!("{comma}{space}")))
+            }
+        }
+    }
+
+    /// Whether `value` fits on one line within `--width` after `used` columns.
+    /// Containers holding `--annotate` comments are always expanded so each
+    /// comment keeps its own line.
+    fn fits_inline(&self, value: &Value, indent_level: usize, used: usize) -> bool {
+        let Some(width) = self.config.width else {
+            return false;
+        };
+        width.checked_sub(used).and_then(|budget| self.flat_width(value, indent_level, budget)).is_some()
+            && !(self.config.annotate && has_annotations(value))
+    }
+
+    /// The width of [`Self::flat`]'s rendering of `value`, or `None` as soon
+    /// as it exceeds `budget`.
+    fn flat_width(&self, value: &Value, indent_level: usize, budget: usize) -> Option<usize> {
+        let width = if self.elided(indent_level) {
+            match value {
+                Value::Object(_) | Value::Array(_) => 2 + elided_summary(value).map_or(0, |s| s.chars().count()),
+                _ => 1,
+            }
+        } else {
+            match value {
+                Value::String(s) => {
+                    let (display, summary) = self.truncate(s);
+                    escape(&display).chars().count() + 2 + summary.map_or(0, |s| s.chars().count())
+                }
+                Value::Array(arr) => {
+                    let shown = self.shown_items(arr.len());
+                    let hidden = arr.len() - shown;
+                    let entries = shown + usize::from(hidden > 0);
+                    let mut width = 2 + 2 * entries.saturating_sub(1);
+                    if hidden > 0 {
+                        width += more_items(hidden).chars().count();
+                    }
+                    for v in arr.iter().take(shown) {
+                        width += self.flat_width(v, indent_level + 1, budget.checked_sub(width)?)?;
+                    }
+                    width
+                }
+                Value::Object(map) => {
+                    let mut width = 2 + 2 * map.len().saturating_sub(1);
+                    for (k, v) in map {
+                        width += escape(k).chars().count() + 4;
+                        width += self.flat_width(v, indent_level + 1, budget.checked_sub(width)?)?;
+                    }
+                    width
+                }
+                scalar => scalar.to_string().len(),
             }
-            let items_str = items.join("\n");
-            let open = colorize("[", TokenKind::Punctuation, theme);
-            let close = colorize("]", TokenKind::Punctuation, theme);
-            Ok(format!("{open}\n{items_str}\n{current_indent}{close}"))
+        };
+        (width <= budget).then_some(width)
+    }
+
+    /// Leading whitespace for `indent_level`. For `--width` a tab counts as
+    /// `indent` columns.
+    fn indentation(&self, indent_level: usize) -> String {
+        match self.config.indent_style {
+            IndentStyle::Space => " ".repeat(indent_level * self.config.indent),
+            IndentStyle::Tab => "\t".repeat(indent_level),
+        }
+    }
+
+    fn elided(&self, indent_level: usize) -> bool {
+        self.config.max_depth.is_some_and(|max| indent_level > max)
+    }
+
+    /// `normal`, or the error kind when the node at `path` is marked.
+    fn kind(&self, path: &JsonPath, normal: TokenKind) -> TokenKind {
+        if self.marks.iter().any(|m| m == path) { TokenKind::Error } else { normal }
+    }
+
+    /// `s` cut to `--max-string-length`, and when it was cut, the summary
+    /// to show after it, such as `… 1,234 chars`.
+    fn truncate(&self, s: &str) -> (String, Option<String>) {
+        let len = s.chars().count();
+        match self.config.max_string_length {
+            Some(max_len) if len > max_len => (s.chars().take(max_len.saturating_sub(1)).collect(), Some(format!("… {}", counted(len, "char")))),
+            _ => (s.to_string(), None),
+        }
+    }
+
+    /// How many items of an array of `len` to show under `--max-items`.
+    fn shown_items(&self, len: usize) -> usize {
+        self.config.max_items.map_or(len, |max| max.min(len))
+    }
+
+    fn entries<'v>(&self, map: &'v serde_json::Map<String, Value>) -> Vec<(&'v String, &'v Value)> {
+        let mut entries: Vec<_> = map.iter().collect();
+        if self.config.sort_keys {
+            entries.sort_by_key(|(k, _)| *k);
+        }
+        entries
+    }
+
+    /// Finish one array item or object entry: the separating comma, then the
+    /// `--annotate` comment for its value, if any.
+    fn entry_line(&self, mut line: String, key: Option<&str>, value: &Value, more: bool, indent_level: usize) -> String {
+        if more {
+            line.push_str(&colorize(",", TokenKind::Comma, self.theme));
         }
-        Value::Object(map) => {
-            let mut entries: Vec<_> = map.iter().collect();
-            if config.sort_keys {
-                entries.sort_by_key(|(k, _)| *k);
+        if self.config.annotate && !self.elided(indent_level + 1) {
+            if let Some(note) = crate::annotate::annotate(key, value) {
+                line.push_str(&format!(" {}", colorize(&format!("// {}", note), TokenKind::Annotation, self.theme)));
             }
-            let mut items = Vec::new();
-            for (k, v) in entries {
-                let key_str = format!("\"{}\"", colorize(k, TokenKind::Key, theme));
-                let colon = colorize(":", TokenKind::Punctuation, theme);
-                let val = format_value(v, config, theme, indent_level + 1)?;
-                items.push(format!("{child_indent}{key_str}{colon} {val}"));
+        }
+        line
+    }
+}
+
+/// `n` with thousands separators, as `3,200`.
+pub(crate) fn group_digits(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// `n` followed by `unit`, pluralised: `1 key`, `12 keys`, `3,200 items`.
+pub(crate) fn counted(n: usize, unit: &str) -> String {
+    format!("{} {}{}", group_digits(n), unit, if n == 1 { "" } else { "s" })
+}
+
+/// The `… 12 keys` or `… 340 items` shown inside an elided container, or
+/// `None` when it is empty and nothing is hidden.
+fn elided_summary(value: &Value) -> Option<String> {
+    let summary = match value {
+        Value::Object(map) if !map.is_empty() => counted(map.len(), "key"),
+        Value::Array(arr) if !arr.is_empty() => counted(arr.len(), "item"),
+        _ => return None,
+    };
+    Some(format!("… {}", summary))
+}
+
+/// The line standing in for the array items cut by `--max-items`.
+fn more_items(hidden: usize) -> String {
+    format!("… {} more", group_digits(hidden))
+}
+
+/// Whether any value inside `value` gets an `--annotate` comment.
+fn has_annotations(value: &Value) -> bool {
+    match value {
+        Value::Array(arr) => arr.iter().any(|v| crate::annotate::annotate(None, v).is_some() || has_annotations(v)),
+        Value::Object(map) => map.iter().any(|(k, v)| crate::annotate::annotate(Some(k), v).is_some() || has_annotations(v)),
+        _ => false,
+    }
+}
+
+/// The JSON escape sequence for `c`, if it needs one inside a string.
+fn escape_char(c: char) -> Option<String> {
+    Some(match c {
+        '"' => "\\\"".to_string(),
+        '\\' => "\\\\".to_string(),
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        '\t' => "\\t".to_string(),
+        '\u{8}' => "\\b".to_string(),
+        '\u{c}' => "\\f".to_string(),
+        c if c.is_control() => format!("\\u{:04x}", c as u32),
+        _ => return None,
+    })
+}
+
+fn escape(s: &str) -> String {
+    s.chars().map(|c| escape_char(c).unwrap_or_else(|| c.to_string())).collect()
+}
+
+/// Escape `s` for output, drawing the text as `kind` and each escape
+/// sequence as [`TokenKind::Escape`].
+fn highlight_escapes(s: &str, kind: TokenKind, theme: &Theme) -> String {
+    let mut out = String::new();
+    let mut run = String::new();
+    for c in s.chars() {
+        match escape_char(c) {
+            Some(seq) => {
+                if !run.is_empty() {
+                    out.push_str(&colorize(&run, kind, theme));
+                    run.clear();
+                }
+                out.push_str(&colorize(&seq, TokenKind::Escape, theme));
             }
-            let items_str = items.join(",\n");
-            let open = colorize("{", TokenKind::Punctuation, theme);
-            let close = colorize("}", TokenKind::Punctuation, theme);
-            Ok(format!("{open}\n{items_str}\n{current_indent}{close}"))
+            None => run.push(c),
+        }
+    }
+    if !run.is_empty() || out.is_empty() {
+        out.push_str(&colorize(&run, kind, theme));
+    }
+    out
+}
+
+/// Recognise strings worth highlighting on their own: URLs, dates and
+/// timestamps, UUIDs and email addresses.
+pub fn string_kind(s: &str) -> TokenKind {
+    if is_url(s) {
+        TokenKind::Url
+    } else if is_uuid(s) {
+        TokenKind::Uuid
+    } else if is_date(s) {
+        TokenKind::Date
+    } else if is_email(s) {
+        TokenKind::Email
+    } else {
+        TokenKind::String
+    }
+}
+
+fn is_url(s: &str) -> bool {
+    match s.split_once("://") {
+        Some((scheme, rest)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                && !rest.is_empty()
+                && !rest.chars().any(char::is_whitespace)
         }
+        None => false,
+    }
+}
+
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12])
+        && groups.iter().all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// `YYYY-MM-DD`, optionally followed by `T` or a space and `HH:MM[:SS[.fff]]`
+/// with an optional `Z` or `±HH:MM` offset.
+fn is_date(s: &str) -> bool {
+    fn digits(s: &str, n: usize) -> bool {
+        s.len() == n && s.chars().all(|c| c.is_ascii_digit())
+    }
+    let (date, time) = match s.find(['T', 't', ' ']) {
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+        None => (s, None),
+    };
+    let parts: Vec<&str> = date.split('-').collect();
+    let date_ok = parts.len() == 3 && digits(parts[0], 4) && digits(parts[1], 2) && digits(parts[2], 2)
+        && matches!(parts[1].parse::<u8>(), Ok(1..=12))
+        && matches!(parts[2].parse::<u8>(), Ok(1..=31));
+    let time = match time {
+        None => return date_ok,
+        Some(time) => time,
+    };
+    let time = time.strip_suffix(['Z', 'z']).unwrap_or(time);
+    let time = match time.rfind(['+', '-']) {
+        Some(i) if digits(&time[i + 1..].replace(':', ""), 4) => &time[..i],
+        _ => time,
+    };
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, "0"));
+    let fields: Vec<&str> = clock.split(':').collect();
+    date_ok
+        && (2..=3).contains(&fields.len())
+        && fields.iter().all(|f| digits(f, 2))
+        && !fraction.is_empty()
+        && fraction.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_email(s: &str) -> bool {
+    match s.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !s.chars().any(|c| c.is_whitespace() || c.is_control())
+        }
+        None => false,
     }
 }
 
@@ -119,6 +521,39 @@ mod tests {
         assert!(result.contains("…"));
     }
 
+    #[test]
+    fn test_elision_summaries() {
+        let items: Vec<u32> = (0..3205).collect();
+        let value = json!({"big": {"items": items, "empty": [], "meta": {"a": 1, "b": 2}}, "s": "x".repeat(1500)});
+        let theme = Theme::plain();
+        let depth = Config {
+            max_depth: Some(1),
+            max_string_length: Some(4),
+            ..Default::default()
+        };
+        let result = format_value(&value, &depth, &theme, 0).unwrap();
+        assert!(result.contains("    \"empty\": [],\n"));
+        assert!(result.contains("    \"items\": [… 3,205 items],\n"));
+        assert!(result.contains("    \"meta\": {… 2 keys}\n"));
+        assert!(result.contains("  \"s\": \"xxx\"… 1,500 chars\n"));
+
+        let first = Config {
+            max_items: Some(3),
+            line_paths: true,
+            ..Default::default()
+        };
+        let result = format_value(&json!({"items": items}), &first, &theme, 0).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[4], "items[2] │     2,");
+        assert_eq!(lines[5], "items    │     … 3,202 more");
+        assert_eq!(lines[6], "items    │   ]");
+
+        let inline = Config { max_items: Some(2), width: Some(80), ..Default::default() };
+        assert_eq!(format_value(&json!([[1, 2, 3, 4], [5]]), &inline, &theme, 0).unwrap(), "[[1, 2, … 2 more], [5]]");
+        assert_eq!(counted(1, "key"), "1 key");
+        assert_eq!(counted(1_234_567, "item"), "1,234,567 items");
+    }
+
     #[test]
     fn test_sort_keys() {
         let value = json!({"z": 1, "a": 2, "m": 3});
@@ -134,4 +569,232 @@ mod tests {
         assert!(lines[2].contains("\"m\""));
         assert!(lines[3].contains("\"z\""));
     }
+
+    #[test]
+    fn test_format_marked() {
+        let value = json!({"a": [1, "x"], "b": null});
+        let config = Config::default();
+        let theme = Theme::new("rainbow", false).unwrap();
+        let marks = vec![vec![PathSegment::Key("a".to_string()), PathSegment::Index(1)]];
+        let result = format_marked(&value, &config, &theme, 0, &marks).unwrap();
+        let marked = colorize("\"x\"", TokenKind::Error, &theme);
+        assert!(result.contains(&marked));
+        assert!(result.contains(&colorize("null", TokenKind::Null, &theme)));
+        assert!(!result.contains(&colorize("]", TokenKind::Error, &theme)));
+    }
+
+    #[test]
+    fn test_string_kinds() {
+        assert_eq!(string_kind("https://example.com/a?b=1"), TokenKind::Url);
+        assert_eq!(string_kind("see https://example.com"), TokenKind::String);
+        assert_eq!(string_kind("550e8400-e29b-41d4-a716-446655440000"), TokenKind::Uuid);
+        assert_eq!(string_kind("2024-02-29"), TokenKind::Date);
+        assert_eq!(string_kind("2024-02-29T13:45:00Z"), TokenKind::Date);
+        assert_eq!(string_kind("2024-02-29 13:45:00.123+02:00"), TokenKind::Date);
+        assert_eq!(string_kind("2024-13-01"), TokenKind::String);
+        assert_eq!(string_kind("12:30"), TokenKind::String);
+        assert_eq!(string_kind("ops@example.com"), TokenKind::Email);
+        assert_eq!(string_kind("@handle"), TokenKind::String);
+        assert_eq!(string_kind("plain text"), TokenKind::String);
+    }
+
+    #[test]
+    fn test_strings_are_escaped() {
+        let value = json!({"quote\"key": "line\nbreak\t\"x\" \\ \u{1}"});
+        let theme = Theme::new("mono", false).unwrap();
+        let result = format_value(&value, &Config::default(), &theme, 0).unwrap();
+        assert!(result.contains(r#""quote\"key": "line\nbreak\t\"x\" \\ \u0001""#), "{}", result);
+        let parsed: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed, value);
+
+        // `--width` counts the escaped text, as printed.
+        let value = json!({"k": "a\"b"});
+        let fits = Config { width: Some(13), ..Default::default() };
+        assert_eq!(format_value(&value, &fits, &theme, 0).unwrap(), r#"{"k": "a\"b"}"#);
+        let narrow = Config { width: Some(12), ..Default::default() };
+        assert_eq!(format_value(&value, &narrow, &theme, 0).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_fine_token_kinds_fall_back_and_override() {
+        let value = json!({"n": [1, 2.5], "u": "https://x.io", "e": "a\nb"});
+        let config = Config::default();
+        let theme = Theme::new("rainbow", false).unwrap();
+        let result = format_value(&value, &config, &theme, 0).unwrap();
+        assert!(result.contains(&colorize("1", TokenKind::Number, &theme)));
+        assert!(result.contains(&colorize("https://x.io", TokenKind::String, &theme)));
+        assert!(result.contains(&colorize("[", TokenKind::Punctuation, &theme)));
+
+        let def: crate::theme::ThemeDef = toml::from_str(r#"
+            inherits = "rainbow"
+            [float]
+            fg = "bright-red"
+            [escape]
+            bold = true
+            [url]
+            underline = true
+            [[rainbow_brackets]]
+            fg = "red"
+            [[rainbow_brackets]]
+            fg = "blue"
+        "#).unwrap();
+        let inline = std::collections::BTreeMap::from([("fine".to_string(), def)]);
+        let theme = crate::theme::ThemeRegistry::with_files(&inline, Default::default()).theme("fine", false).unwrap();
+        let result = format_value(&value, &config, &theme, 0).unwrap();
+        assert_eq!(theme.url, theme.string.underline());
+        assert_eq!(theme.escape, theme.string.bold());
+        assert!(result.contains(&colorize("2.5", TokenKind::Float, &theme)));
+        assert!(result.contains(&colorize("1", TokenKind::Integer, &theme)));
+        assert!(result.contains(&colorize("https://x.io", TokenKind::Url, &theme)));
+        assert!(result.contains(&colorize("\\n", TokenKind::Escape, &theme)));
+        assert!(result.starts_with(&colorize("{", TokenKind::Bracket(0), &theme)));
+        assert!(result.contains(&colorize("[", TokenKind::Bracket(1), &theme)));
+        assert_ne!(colorize("[", TokenKind::Bracket(0), &theme), colorize("[", TokenKind::Bracket(1), &theme));
+        assert_eq!(colorize("[", TokenKind::Bracket(2), &theme), colorize("[", TokenKind::Bracket(0), &theme));
+    }
+
+    #[test]
+    fn test_annotate_adds_trailing_comments() {
+        let value = json!({"created": 1_792_227_600, "size_bytes": 2048, "name": "x", "ids": [1_792_227_600_000i64, 5]});
+        let config = Config {
+            annotate: true,
+            ..Default::default()
+        };
+        let theme = Theme::plain();
+        let result = format_value(&value, &config, &theme, 0).unwrap();
+        assert!(result.contains("\"created\": 1792227600, // 2026-10-17T09:00:00Z\n"), "{}", result);
+        assert!(result.contains("\"size_bytes\": 2048 // 2.0 KiB\n"), "{}", result);
+        assert!(result.contains("1792227600000, // 2026-10-17T09:00:00Z\n"), "{}", result);
+        assert!(result.contains("\"name\": \"x\",\n"), "{}", result);
+
+        let plain = format_value(&value, &Config::default(), &theme, 0).unwrap();
+        assert!(!plain.contains("//"));
+
+        let mono = Theme::new("mono", false).unwrap();
+        let dimmed = format_value(&value, &config, &mono, 0).unwrap();
+        assert!(dimmed.contains(&colorize("// 2.0 KiB", TokenKind::Annotation, &mono)));
+        assert_eq!(mono.annotation, owo_colors::Style::new().dimmed());
+    }
+
+    #[test]
+    fn test_line_number_and_path_gutters() {
+        let value = json!({"a": [1, {"b": null}], "e": [], "deep": {"x": {"y": 1}}});
+        let theme = Theme::plain();
+        let numbered = Config {
+            line_numbers: true,
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let result = format_value(&value, &numbered, &theme, 0).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], " 1 │ {");
+        assert_eq!(lines[2], " 3 │     …,");
+        assert_eq!(lines[6], " 7 │     \"x\": {… 1 key}");
+        assert_eq!(lines[9], "10 │ }");
+
+        let with_paths = Config {
+            line_paths: true,
+            ..Default::default()
+        };
+        let result = format_value(&value, &with_paths, &theme, 0).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "(root)   │ {");
+        assert_eq!(lines[4], "a[1].b   │       \"b\": null");
+        assert_eq!(lines[5], "a[1]     │     }");
+        assert_eq!(lines[9], "deep.x.y │       \"y\": 1");
+        assert_eq!(lines[12], "e        │   \"e\": []");
+        assert_eq!(lines[13], "(root)   │ }");
+        assert_eq!(gutter_path(&vec![PathSegment::Key("k".repeat(50))]), format!("…{}", "k".repeat(39)));
+
+        let def: crate::theme::ThemeDef = toml::from_str("[annotation]\nfg = \"blue\"\n").unwrap();
+        let inline = std::collections::BTreeMap::from([("g".to_string(), def)]);
+        let themed = crate::theme::ThemeRegistry::with_files(&inline, Default::default()).theme("g", false).unwrap();
+        assert_eq!(themed.gutter, themed.annotation.dimmed());
+        let result = format_value(&json!([]), &numbered, &themed, 0).unwrap();
+        assert_eq!(result, format!("{} []", colorize("1 │", TokenKind::Gutter, &themed)));
+        assert_eq!(format_value(&json!({}), &Config::default(), &theme, 0).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_empty_containers_stay_on_one_line() {
+        let value = json!({"list": [], "map": {}, "nested": [{}]});
+        let result = format_value(&value, &Config::default(), &Theme::plain(), 0).unwrap();
+        assert_eq!(result, "{\n  \"list\": [],\n  \"map\": {},\n  \"nested\": [\n    {}\n  ]\n}");
+    }
+
+    #[test]
+    fn test_width_keeps_short_containers_inline() {
+        let value = json!({"tags": ["a", "b"], "matrix": [[1, 2], [3, 4]], "empty": {}, "long": ["aaaaaaaaaa", "bbbbbbbbbb"]});
+        let theme = Theme::plain();
+        let config = Config {
+            width: Some(30),
+            ..Default::default()
+        };
+        let result = format_value(&value, &config, &theme, 0).unwrap();
+        assert_eq!(result, "{\n  \"empty\": {},\n  \"long\": [\n    \"aaaaaaaaaa\",\n    \"bbbbbbbbbb\"\n  ],\n  \"matrix\": [[1, 2], [3, 4]],\n  \"tags\": [\"a\", \"b\"]\n}");
+
+        // Exactly at the limit, including the trailing comma.
+        let fits = Config { width: Some(29), ..config.clone() };
+        assert!(format_value(&value, &fits, &theme, 0).unwrap().contains("  \"matrix\": [[1, 2], [3, 4]],\n"));
+        let narrow = Config { width: Some(28), ..config.clone() };
+        assert!(format_value(&value, &narrow, &theme, 0).unwrap().contains("  \"matrix\": [\n    [1, 2],\n"));
+        let wide = Config { width: Some(200), ..config.clone() };
+        let one_line = format_value(&value, &wide, &theme, 0).unwrap();
+        assert_eq!(one_line.lines().count(), 1);
+        assert_eq!(serde_json::from_str::<Value>(&one_line).unwrap(), value);
+
+        let rainbow = Theme::new("rainbow", false).unwrap();
+        let colored = format_value(&json!({"n": [1, 2.5]}), &wide, &rainbow, 0).unwrap();
+        assert!(colored.contains(&colorize("2.5", TokenKind::Float, &rainbow)));
+        assert!(colored.contains(&colorize("[", TokenKind::Bracket(1), &rainbow)));
+
+        let limited = Config { max_depth: Some(1), max_string_length: Some(3), ..wide.clone() };
+        assert_eq!(format_value(&json!({"a": {"b": {"c": 1}}, "s": "abcdef"}), &limited, &theme, 0).unwrap(), "{\"a\": {\"b\": {… 1 key}}, \"s\": \"ab\"… 6 chars}");
+
+        let annotated = Config { annotate: true, ..wide };
+        assert!(format_value(&json!({"t": [1_792_227_600]}), &annotated, &theme, 0).unwrap().contains("\n    1792227600 // 2026"));
+    }
+
+    #[test]
+    fn test_compact_uses_theme_and_limits() {
+        let value = json!({"b": {"deep": [1, 2]}, "a": "abcdef", "n": null});
+        let config = Config {
+            compact: true,
+            max_depth: Some(1),
+            max_string_length: Some(4),
+            sort_keys: true,
+            line_numbers: true,
+            ..Default::default()
+        };
+        let plain = format_value(&value, &config, &Theme::plain(), 0).unwrap();
+        assert_eq!(plain, r#"{"a":"abc"… 6 chars,"b":{"deep":[… 2 items]},"n":null}"#);
+
+        let rainbow = Theme::new("rainbow", false).unwrap();
+        let colored = format_value(&value, &config, &rainbow, 0).unwrap();
+        assert!(!colored.contains('\n'));
+        assert!(colored.contains(&format!("\"{}\"{}", colorize("a", TokenKind::Key, &rainbow), colorize(":", TokenKind::Colon, &rainbow))));
+        assert!(colored.contains(&colorize("null", TokenKind::Null, &rainbow)));
+
+        let marked = format_marked(&json!([1, 2]), &config, &rainbow, 0, &[vec![PathSegment::Index(1)]]).unwrap();
+        assert!(marked.ends_with(&format!("{}{}", colorize("2", TokenKind::Error, &rainbow), colorize("]", TokenKind::Bracket(0), &rainbow))));
+    }
+
+    #[test]
+    fn test_tab_indentation() {
+        let value = json!({"a": {"b": [1, 2]}});
+        let config = Config {
+            indent: 4,
+            indent_style: IndentStyle::Tab,
+            ..Default::default()
+        };
+        let result = format_value(&value, &config, &Theme::plain(), 0).unwrap();
+        assert_eq!(result, "{\n\t\"a\": {\n\t\t\"b\": [\n\t\t\t1,\n\t\t\t2\n\t\t]\n\t}\n}");
+
+        // Tabs count as `indent` columns against --width: "\t\t\"b\": [1, 2]" is 8 + 11 wide.
+        let fits = Config { width: Some(19), ..config.clone() };
+        assert!(format_value(&value, &fits, &Theme::plain(), 0).unwrap().contains("\t\t\"b\": [1, 2]\n"));
+        let narrow = Config { width: Some(18), ..config };
+        assert!(format_value(&value, &narrow, &Theme::plain(), 0).unwrap().contains("\t\t\"b\": [\n"));
+    }
 }
@@ -1,9 +1,21 @@
+pub mod annotate;
 pub mod cli;
 pub mod config;
+pub mod diagnostic;
+pub mod editorconfig;
 pub mod error;
+pub mod flatten;
 pub mod formatter;
+pub mod markup;
+pub mod pager;
 pub mod path;
+pub mod recover;
+pub mod shape;
+pub mod stats;
+pub mod tabular;
+pub mod terminal;
 pub mod theme;
+pub mod viewer;
 
 pub use error::JsonfizzError;
 
@@ -16,23 +28,192 @@ use jsonschema::JSONSchema;
 use serde_json::Value;
 
 pub fn run<W: Write>(args: cli::CliArgs, mut writer: W) -> Result<(), JsonfizzError> {
-    let config = args.to_config();
+    let config = resolve_config(&args, None);
+    let to_terminal = atty::is(atty::Stream::Stdout);
+    let theme = select_theme(&config)?;
+    if args.interactive {
+        return run_interactive(&args.files, &config, &theme);
+    }
+    let paging = config.paging.unwrap_or(cli::PagingChoice::Auto);
+    if paging == cli::PagingChoice::Never || (paging == cli::PagingChoice::Auto && !to_terminal) {
+        return process_inputs(&args, &config, &theme, &mut writer);
+    }
+    // Buffer the output to see whether it needs a pager; what was formatted
+    // before an error is still shown.
+    let mut buffer = Vec::new();
+    let result = process_inputs(&args, &config, &theme, &mut buffer);
+    let fits = paging == cli::PagingChoice::Auto && !crate::pager::taller_than_screen(&buffer);
+    if fits || !crate::pager::page(&buffer)? {
+        writer.write_all(&buffer)?;
+    }
+    result
+}
+
+/// The config for `file` (or for stdin), with `--width` defaulting to the
+/// terminal's width when writing to one.
+fn resolve_config(args: &cli::CliArgs, file: Option<&Path>) -> crate::config::Config {
+    let mut config = args.to_config_for(file);
+    if config.width.is_none() && atty::is(atty::Stream::Stdout) {
+        config.width = crossterm::terminal::size().ok().map(|(columns, _)| columns as usize);
+    }
+    config
+}
+
+/// Open the single input (a file, or stdin) in the interactive viewer.
+fn run_interactive(files: &[String], config: &crate::config::Config, theme: &crate::theme::Theme) -> Result<(), JsonfizzError> {
+    if files.len() > 1 {
+        return Err(JsonfizzError::Data("--interactive takes a single input".to_string()));
+    }
+    let (input, name) = read_source(files.first().map(String::as_str))?;
+    let (value, _) = parse_document(&input, name, config, theme)?;
+    let value = apply_get(&value, &config.get)?;
+    validate_schema(&value, config)?;
+    crate::viewer::run(value, theme)
+}
+
+/// Read a file whole, naming it in any error.
+fn read_file(path: &str) -> Result<String, JsonfizzError> {
+    std::fs::read_to_string(path).map_err(|e| JsonfizzError::file_io(path, e))
+}
+
+/// Read one input whole: the named file, or stdin for `None` or `-`.
+/// Returns the text and the name to report it under.
+fn read_source(file: Option<&str>) -> Result<(String, &str), JsonfizzError> {
+    match file {
+        Some(file) if file != "-" => Ok((read_file(file)?, file)),
+        _ => {
+            let mut buffer = Vec::new();
+            io::stdin().lock().read_to_end(&mut buffer)?;
+            let input = String::from_utf8(buffer)
+                .map_err(|e| JsonfizzError::parse_error("UTF-8", e.utf8_error().to_string(), None, None))?;
+            Ok((input, "<stdin>"))
+        }
+    }
+}
+
+/// `jsonfizz stats`: print an overview of each input's structure, as a
+/// table or, with `--json`, as one JSON document per input.
+pub fn stats<W: Write>(args: &cli::CliArgs, stats_args: &cli::StatsArgs, mut writer: W) -> Result<(), JsonfizzError> {
+    let sources: Vec<Option<&str>> = if stats_args.files.is_empty() {
+        vec![None]
+    } else {
+        stats_args.files.iter().map(|file| Some(file.as_str())).collect()
+    };
+    for (i, source) in sources.iter().enumerate() {
+        let config = resolve_config(args, source.filter(|file| *file != "-").map(Path::new));
+        let theme = select_theme(&config)?;
+        let (input, name) = read_source(*source)?;
+        let (value, _) = parse_document(&input, name, &config, &theme)?;
+        let value = apply_get(&value, &config.get)?;
+        let stats = crate::stats::collect(&value, stats_args.top);
+        if stats_args.json {
+            let value = serde_json::to_value(&stats)
+                .map_err(|e| JsonfizzError::Data(format!("Failed to serialize statistics: {}", e)))?;
+            let output = crate::formatter::format_value(&value, &config, &theme, 0)?;
+            write!(writer, "{}", finish_output(&output, &input, &config))?;
+        } else {
+            if sources.len() > 1 {
+                if i > 0 {
+                    writeln!(writer)?;
+                }
+                writeln!(writer, "==> {} <==", name)?;
+            }
+            write!(writer, "{}", crate::stats::render_table(&stats, &theme))?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the configured theme for the terminal's background, or a plain
+/// theme when colors are off. HTML and SVG output is always colored at full
+/// depth unless `--raw` is given, since it does not go to the terminal.
+fn select_theme(config: &crate::config::Config) -> Result<crate::theme::Theme, JsonfizzError> {
+    let markup = crate::markup::is_markup_format(&config.format);
+    let raw = config.raw || (!markup && !use_colors(config));
+    let background = if raw {
+        crate::theme::Background::default()
+    } else if markup {
+        markup_background(config)
+    } else {
+        background(config)
+    };
+    let mut registry = crate::theme::ThemeRegistry::load(&config.themes).with_background(background);
+    if markup {
+        registry = registry.with_color_depth(crate::theme::ColorDepth::TrueColor);
+    }
+    registry.theme(config.theme_for(background), raw)
+}
+
+fn background(config: &crate::config::Config) -> crate::theme::Background {
+    match config.background {
+        Some(cli::BackgroundChoice::Light) => crate::theme::Background::Light,
+        Some(cli::BackgroundChoice::Dark) => crate::theme::Background::Dark,
+        Some(cli::BackgroundChoice::Auto) | None => crate::terminal::detect_background().unwrap_or_default(),
+    }
+}
 
-    // Determine if colors should be used
-    let use_colors = match config.color {
+/// The page background for HTML and SVG output: dark unless `light` is chosen.
+fn markup_background(config: &crate::config::Config) -> crate::theme::Background {
+    match config.background {
+        Some(cli::BackgroundChoice::Light) => crate::theme::Background::Light,
+        _ => crate::theme::Background::Dark,
+    }
+}
+
+fn use_colors(config: &crate::config::Config) -> bool {
+    match config.color {
         Some(cli::ColorChoice::Always) => true,
         Some(cli::ColorChoice::Never) => false,
         Some(cli::ColorChoice::Auto) | None => {
             // Auto-detect: use colors if stdout is TTY and NO_COLOR is not set
             std::env::var("NO_COLOR").is_err() && atty::is(atty::Stream::Stdout)
         }
+    }
+}
+
+/// Print every available theme with a preview on a small sample document.
+pub fn list_themes<W: Write>(args: &cli::CliArgs, mut writer: W) -> Result<(), JsonfizzError> {
+    let config = args.to_config();
+    let raw = config.raw || !use_colors(&config);
+    let background = if raw { crate::theme::Background::default() } else { background(&config) };
+    let registry = crate::theme::ThemeRegistry::load(&config.themes).with_background(background);
+    let sample = serde_json::json!({
+        "name": "jsonfizz",
+        "version": 2.1,
+        "stable": true,
+        "license": null,
+        "tags": ["json", "cli"],
+    });
+    let preview_config = crate::config::Config {
+        indent: config.indent,
+        ..Default::default()
     };
+    for name in registry.names() {
+        let label = if registry.is_user_theme(&name) { format!("{} (user)", name) } else { name.clone() };
+        writeln!(writer, "{}", label)?;
+        match registry.theme(&name, raw) {
+            Ok(theme) => {
+                let preview = crate::formatter::format_value(&sample, &preview_config, &theme, 0)?;
+                for line in preview.lines() {
+                    writeln!(writer, "  {}", line)?;
+                }
+            }
+            Err(e) => writeln!(writer, "  error: {}", e)?,
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
 
-    let theme = crate::theme::Theme::new(&config.theme, config.raw || !use_colors)?;
-    process_inputs(&args.files, &config, &theme, &mut writer)
+pub fn print_config<W: Write>(args: &cli::CliArgs, mut writer: W) -> Result<(), JsonfizzError> {
+    let file = args.files.first().filter(|file| *file != "-").map(Path::new);
+    let layers = crate::config::load_layers_for(file)?;
+    write!(writer, "{}", crate::config::describe(args, &layers))?;
+    Ok(())
 }
 
-fn process_inputs<W: Write>(files: &[String], config: &crate::config::Config, theme: &crate::theme::Theme, writer: &mut W) -> Result<(), JsonfizzError> {
+fn process_inputs<W: Write>(args: &cli::CliArgs, config: &crate::config::Config, theme: &crate::theme::Theme, writer: &mut W) -> Result<(), JsonfizzError> {
+    let files = &args.files;
     if files.is_empty() {
         // For stdin, read efficiently and warn about large inputs
         let stdin = io::stdin();
@@ -72,13 +253,21 @@ fn process_inputs<W: Write>(files: &[String], config: &crate::config::Config, th
         // Try to parse based on input format
         let input_str = std::str::from_utf8(&buffer)
             .map_err(|e| JsonfizzError::parse_error("UTF-8", e.to_string(), None, None))?;
-        let value: serde_json::Value = parse_input(input_str, &config.input_format)?;
+        let (value, marks) = parse_document(input_str, "<stdin>", config, theme)?;
         let value = apply_get(&value, &config.get)?;
+        let marks = rebase_marks(marks, &config.get)?;
         validate_schema(&value, config)?;
-        let output = format_output(&value, config, theme)?;
-        writeln!(writer, "{}", output)?;
+        let output = render_document(args, &value, config, theme, &marks)?;
+        write!(writer, "{}", finish_output(&output, input_str, config))?;
     } else {
         for file in files {
+            let file_config;
+            let config = if file == "-" {
+                config
+            } else {
+                file_config = resolve_config(args, Some(Path::new(file)));
+                &file_config
+            };
             let input = if file == "-" {
                 let stdin = io::stdin();
                 let mut reader = stdin.lock();
@@ -88,26 +277,114 @@ fn process_inputs<W: Write>(files: &[String], config: &crate::config::Config, th
                     .map_err(|e| JsonfizzError::parse_error("UTF-8", e.utf8_error().to_string(), None, None))?
             } else {
                 // Check file size before reading
-                let metadata = std::fs::metadata(file)?;
+                let metadata = std::fs::metadata(file).map_err(|e| JsonfizzError::file_io(file, e))?;
                 let file_size = metadata.len();
 
                 if file_size > 50 * 1024 * 1024 { // 50MB
                     eprintln!("Warning: Large file detected ({} MB): {}. Consider using --max-depth for better performance.", file_size / (1024 * 1024), file);
                 }
 
-                std::fs::read_to_string(file)?
+                read_file(file)?
             };
-            let value: serde_json::Value = parse_input(&input, &config.input_format)?;
+            let name = if file == "-" { "<stdin>" } else { file.as_str() };
+            let (value, marks) = parse_document(&input, name, config, theme)?;
             let value = apply_get(&value, &config.get)?;
+            let marks = rebase_marks(marks, &config.get)?;
             validate_schema(&value, config)?;
-            let output = format_output(&value, config, theme)?;
-            writeln!(writer, "{}", output)?;
+            let output = render_document(args, &value, config, theme, &marks)?;
+            write!(writer, "{}", finish_output(&output, &input, config))?;
         }
     }
     Ok(())
 }
 
-fn parse_input(input: &str, format: &str) -> Result<serde_json::Value, JsonfizzError> {
+/// One document's output: its `--shape`, or the document in the output
+/// format after `--flatten` or `--unflatten`.
+fn render_document(args: &cli::CliArgs, value: &Value, config: &crate::config::Config, theme: &crate::theme::Theme, marks: &[crate::path::JsonPath]) -> Result<String, JsonfizzError> {
+    let transformed = if args.flatten {
+        Value::Object(crate::flatten::flatten(value, &config.key_separator)?)
+    } else if args.unflatten {
+        crate::flatten::unflatten(value, &config.key_separator)?
+    } else {
+        return render_value(args, value, config, theme, marks);
+    };
+    // Repaired regions cannot be located in the reshaped document.
+    render_value(args, &transformed, config, theme, &[])
+}
+
+fn render_value(args: &cli::CliArgs, value: &Value, config: &crate::config::Config, theme: &crate::theme::Theme, marks: &[crate::path::JsonPath]) -> Result<String, JsonfizzError> {
+    if args.shape {
+        return Ok(crate::shape::render(value, config, theme));
+    }
+    format_output_marked(value, config, theme, marks)
+}
+
+/// Give one document's output its final newline and line endings.
+/// `input` is the document as read, which `--newline auto` follows.
+fn finish_output(output: &str, input: &str, config: &crate::config::Config) -> String {
+    let mut text = output.trim_end_matches('\n').to_string();
+    if config.final_newline {
+        text.push('\n');
+    }
+    let crlf = match config.newline {
+        cli::NewlineStyle::Lf => false,
+        cli::NewlineStyle::Crlf => true,
+        cli::NewlineStyle::Auto => input.contains("\r\n"),
+    };
+    if crlf {
+        text = text.replace('\n', "\r\n");
+    }
+    text
+}
+
+/// How many `--lenient` repairs to list on stderr before summarizing the rest.
+const MAX_REPAIRS_SHOWN: usize = 20;
+
+/// Parse one input document, attaching source context to parse errors.
+///
+/// In lenient mode the document is recovered instead of rejected; the repairs
+/// are reported on stderr and their paths are returned so the formatter can
+/// mark them.
+fn parse_document(input: &str, name: &str, config: &crate::config::Config, theme: &crate::theme::Theme) -> Result<(Value, Vec<crate::path::JsonPath>), JsonfizzError> {
+    if !config.lenient {
+        let value = parse_input(input, config).map_err(|e| e.with_source(name, input, theme))?;
+        return Ok((value, Vec::new()));
+    }
+    if config.input_format != "json" {
+        return Err(JsonfizzError::Config(format!("--lenient is only supported for JSON input, not {}", config.input_format)));
+    }
+    let recovered = crate::recover::parse_lenient(input).map_err(|e| e.with_source(name, input, theme))?;
+    if !recovered.repairs.is_empty() {
+        eprintln!("Warning: recovered {} with {} repair(s):", name, recovered.repairs.len());
+        for repair in recovered.repairs.iter().take(MAX_REPAIRS_SHOWN) {
+            let path = crate::path::format_path(&repair.path);
+            if path.is_empty() {
+                eprintln!("  line {}, column {}: {}", repair.line, repair.column, repair.message);
+            } else {
+                eprintln!("  line {}, column {}: {} at {}", repair.line, repair.column, repair.message, path);
+            }
+        }
+        if recovered.repairs.len() > MAX_REPAIRS_SHOWN {
+            eprintln!("  …and {} more", recovered.repairs.len() - MAX_REPAIRS_SHOWN);
+        }
+    }
+    let marks = recovered.repairs.into_iter().map(|r| r.path).collect();
+    Ok((recovered.value, marks))
+}
+
+/// Make mark paths relative to the `--get` target, dropping marks outside it.
+fn rebase_marks(marks: Vec<crate::path::JsonPath>, get_path: &Option<String>) -> Result<Vec<crate::path::JsonPath>, JsonfizzError> {
+    match get_path.as_deref() {
+        Some(path_str) if !marks.is_empty() => {
+            let prefix = crate::path::parse_path(path_str)?;
+            Ok(marks.into_iter().filter(|m| m.starts_with(&prefix)).map(|m| m[prefix.len()..].to_vec()).collect())
+        }
+        _ => Ok(marks),
+    }
+}
+
+fn parse_input(input: &str, config: &crate::config::Config) -> Result<serde_json::Value, JsonfizzError> {
+    let format = config.input_format.as_str();
     match format {
         "json" => serde_json::from_str(input).map_err(json_parse_error),
         "yaml" => serde_yaml::from_str(input).map_err(yaml_parse_error),
@@ -116,14 +393,14 @@ fn parse_input(input: &str, format: &str) -> Result<serde_json::Value, JsonfizzE
             serde_json::to_value(toml_value)
                 .map_err(|e| JsonfizzError::Data(format!("TOML to JSON conversion error: {}", e)))
         }
-        "csv" => parse_csv_to_json(input),
+        "csv" => crate::tabular::parse(input, config),
         _ => Err(JsonfizzError::Config(format!("Unsupported input format: {}. Supported: json, yaml, toml, csv", format))),
     }
 }
 
 fn json_parse_error(err: serde_json::Error) -> JsonfizzError {
     let line = if err.line() > 0 { Some(err.line()) } else { None };
-    let column = if err.column() > 0 { Some(err.column() + 1) } else { None };
+    let column = if err.column() > 0 { Some(err.column()) } else { None };
     JsonfizzError::parse_error("JSON", err.to_string(), line, column)
 }
 
@@ -141,54 +418,21 @@ fn toml_parse_error(err: toml::de::Error) -> JsonfizzError {
     JsonfizzError::parse_error("TOML", err.to_string(), line, column)
 }
 
-fn csv_parse_error(err: csv::Error) -> JsonfizzError {
-    let (line, column) = if let Some(pos) = err.position() {
-        (Some(pos.line() as usize), None)
-    } else {
-        (None, None)
-    };
-    JsonfizzError::parse_error("CSV", err.to_string(), line, column)
-}
-
-fn parse_csv_to_json(input: &str) -> Result<Value, JsonfizzError> {
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_reader(input.as_bytes());
-
-    let headers = reader.headers().map_err(csv_parse_error)?.clone();
-    let mut rows = Vec::new();
-
-    for (index, record) in reader.records().enumerate() {
-        let record = record.map_err(csv_parse_error)?;
-        if record.len() != headers.len() {
-            return Err(JsonfizzError::parse_error(
-                "CSV",
-                format!("Record {} has {} fields, expected {}", index + 1, record.len(), headers.len()),
-                Some(index + 2), // +2 to account for header line
-                None,
-            ));
-        }
-
-        let mut obj = serde_json::Map::new();
-        for (header, value) in headers.iter().zip(record.iter()) {
-            obj.insert(header.to_string(), Value::String(value.to_string()));
-        }
-        rows.push(Value::Object(obj));
-    }
-
-    Ok(Value::Array(rows))
-}
-
 fn validate_schema(value: &Value, config: &crate::config::Config) -> Result<(), JsonfizzError> {
     let schema_path = match &config.schema {
         Some(path) => path,
         None => return Ok(()),
     };
 
-    let schema_str = std::fs::read_to_string(schema_path)?;
-    let schema_json: Value = serde_json::from_str(&schema_str).map_err(json_parse_error)?;
+    let schema_str = read_file(schema_path)?;
+    let schema_json: Value = match serde_json::from_str(&schema_str) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(json_parse_error(e).with_source(schema_path, &schema_str, &crate::theme::Theme::plain()));
+        }
+    };
     let compiled = JSONSchema::compile(&schema_json)
-        .map_err(|e| JsonfizzError::Config(format!("Invalid JSON Schema in {}: {}", schema_path, e)))?;
+        .map_err(|e| JsonfizzError::Schema(format!("Invalid JSON Schema in {}: {}", schema_path, e)))?;
 
     if let Err(errors) = compiled.validate(value) {
         let detail = errors.into_iter().next().map(|err| {
@@ -199,7 +443,7 @@ fn validate_schema(value: &Value, config: &crate::config::Config) -> Result<(),
                 format!("Schema validation failed at {}: {}", path, err)
             }
         }).unwrap_or_else(|| "Schema validation failed".to_string());
-        return Err(JsonfizzError::Config(detail));
+        return Err(JsonfizzError::Schema(detail));
     }
 
     Ok(())
@@ -214,67 +458,45 @@ fn apply_get(value: &serde_json::Value, get_path: &Option<String>) -> Result<ser
     }
 }
 
-fn convert_to_csv(value: &serde_json::Value) -> Result<String, JsonfizzError> {
-    match value {
-        serde_json::Value::Array(arr) => {
-            if arr.is_empty() {
-                return Ok(String::new());
-            }
-
-            // Collect all unique keys from all objects
-            let mut all_keys = std::collections::BTreeSet::new();
-            for item in arr {
-                if let serde_json::Value::Object(obj) = item {
-                    for key in obj.keys() {
-                        all_keys.insert(key.clone());
-                    }
-                }
-            }
-
-            let keys: Vec<String> = all_keys.into_iter().collect();
-
-            // Create CSV writer
-            let mut wtr = csv::WriterBuilder::new()
-                .has_headers(true)
-                .from_writer(vec![]);
-
-            // Write headers
-            wtr.write_record(&keys)
-                .map_err(|e| JsonfizzError::Data(format!("CSV header write error: {}", e)))?;
-
-            // Write data rows
-            for item in arr {
-                if let serde_json::Value::Object(obj) = item {
-                    let mut row = Vec::new();
-                    for key in &keys {
-                        let value = obj.get(key)
-                            .and_then(|v| match v {
-                                serde_json::Value::String(s) => Some(s.clone()),
-                                serde_json::Value::Number(n) => Some(n.to_string()),
-                                serde_json::Value::Bool(b) => Some(b.to_string()),
-                                serde_json::Value::Null => Some(String::new()),
-                                _ => Some(v.to_string()),
-                            })
-                            .unwrap_or_default();
-                        row.push(value);
-                    }
-                    wtr.write_record(&row)
-                        .map_err(|e| JsonfizzError::Data(format!("CSV row write error: {}", e)))?;
-                }
-            }
+/// Write an array of objects as CSV: one row per object, with nested
+/// objects flattened into `a.b` columns in the order keys are first seen, or
+/// the `--columns` paths in the order given. Arrays and other nested values
+/// are written as compact JSON.
+fn convert_to_csv(value: &serde_json::Value, config: &crate::config::Config) -> Result<String, JsonfizzError> {
+    let (headers, rows) = crate::tabular::records(value, config, "CSV")?;
+    if rows.is_empty() {
+        return Ok(String::new());
+    }
 
-            let csv_data = wtr.into_inner()
-                .map_err(|e| JsonfizzError::Data(format!("CSV writer error: {}", e)))?;
-            String::from_utf8(csv_data)
-                .map_err(|e| JsonfizzError::parse_error("UTF-8", e.to_string(), None, None))
-        }
-        _ => Err(JsonfizzError::Config("CSV output requires a JSON array of objects".to_string())),
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(crate::tabular::delimiter(config)?)
+        .quote(crate::tabular::quote(config)?)
+        .from_writer(vec![]);
+    wtr.write_record(&headers)
+        .map_err(|e| JsonfizzError::Data(format!("CSV header write error: {}", e)))?;
+    for row in rows {
+        let cells = row.into_iter().map(|cell| match cell {
+            Some(Value::String(s)) => s,
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        });
+        wtr.write_record(cells)
+            .map_err(|e| JsonfizzError::Data(format!("CSV row write error: {}", e)))?;
     }
+
+    let csv_data = wtr.into_inner()
+        .map_err(|e| JsonfizzError::Data(format!("CSV writer error: {}", e)))?;
+    String::from_utf8(csv_data)
+        .map_err(|e| JsonfizzError::parse_error("UTF-8", e.to_string(), None, None))
 }
 
 fn format_output(value: &serde_json::Value, config: &crate::config::Config, theme: &crate::theme::Theme) -> Result<String, JsonfizzError> {
+    format_output_marked(value, config, theme, &[])
+}
+
+fn format_output_marked(value: &serde_json::Value, config: &crate::config::Config, theme: &crate::theme::Theme, marks: &[crate::path::JsonPath]) -> Result<String, JsonfizzError> {
     match config.format.as_str() {
-        "json" => crate::formatter::format_value(value, config, theme, 0),
+        "json" => crate::formatter::format_marked(value, config, theme, 0, marks),
         "yaml" => {
             let yaml = serde_yaml::to_string(value)
                 .map_err(|e| JsonfizzError::Data(format!("YAML serialization error: {}", e)))?;
@@ -286,9 +508,22 @@ fn format_output(value: &serde_json::Value, config: &crate::config::Config, them
             Ok(toml)
         }
         "csv" => {
-            convert_to_csv(value)
+            convert_to_csv(value, config)
         }
-        _ => Err(JsonfizzError::Config(format!("Unsupported format: {}. Supported: json, yaml, toml, csv", config.format))),
+        "lines" => crate::flatten::to_lines(value, config, theme),
+        "table" | "markdown" => crate::tabular::to_table(value, config, theme, config.format == "markdown"),
+        "html" | "svg" => {
+            // The page draws its own line numbers.
+            let text_config = crate::config::Config { line_numbers: false, line_paths: false, ..config.clone() };
+            let highlighted = crate::formatter::format_marked(value, &text_config, theme, 0, marks)?;
+            let background = markup_background(config);
+            Ok(if config.format == "html" {
+                crate::markup::to_html(&highlighted, config.line_numbers, background)
+            } else {
+                crate::markup::to_svg(&highlighted, config.line_numbers, background)
+            })
+        }
+        _ => Err(JsonfizzError::Config(format!("Unsupported format: {}. Supported: json, yaml, toml, csv, html, svg, lines, table, markdown", config.format))),
     }
 }
 
@@ -306,8 +541,10 @@ pub fn run_benchmarks() {
     let start = Instant::now();
     for _ in 0..1000 {
         let value: serde_json::Value = serde_json::from_str(small_json).unwrap();
-        let mut config = crate::config::Config::default();
-        config.theme = "mono".to_string();
+        let config = crate::config::Config {
+            theme: "mono".to_string(),
+            ..Default::default()
+        };
         let theme = crate::theme::Theme::new("mono", false).unwrap();
         format_output(&value, &config, &theme).unwrap();
     }
@@ -323,14 +560,18 @@ pub fn run_benchmarks() {
     let test_theme = crate::theme::Theme::new("default", false).unwrap();
 
     let start = Instant::now();
-    let mut config_json = crate::config::Config::default();
-    config_json.format = "json".to_string();
+    let config_json = crate::config::Config {
+        format: "json".to_string(),
+        ..Default::default()
+    };
     format_output(&test_value, &config_json, &test_theme).unwrap();
     let json_time = start.elapsed();
 
     let start = Instant::now();
-    let mut config_yaml = crate::config::Config::default();
-    config_yaml.format = "yaml".to_string();
+    let config_yaml = crate::config::Config {
+        format: "yaml".to_string(),
+        ..Default::default()
+    };
     format_output(&test_value, &config_yaml, &test_theme).unwrap();
     let yaml_time = start.elapsed();
 
@@ -344,10 +585,11 @@ pub fn run_benchmarks() {
 
 pub fn run_watch(path: String, args: cli::CliArgs) -> Result<(), JsonfizzError> {
     let config = args.to_config();
+    let theme = select_theme(&config)?;
 
     // Initial format
     println!("🔄 Initial format of {}", path);
-    if let Err(e) = process_file(&path, &config) {
+    if let Err(e) = process_file(&path, &config, &theme) {
         eprintln!("Initial format error: {}", e);
     }
 
@@ -370,7 +612,7 @@ pub fn run_watch(path: String, args: cli::CliArgs) -> Result<(), JsonfizzError>
                 match event.kind {
                     EventKind::Modify(_) | EventKind::Create(_) => {
                         println!("\n🔄 File changed, reformatting...");
-                        if let Err(e) = process_file(&path, &config) {
+                        if let Err(e) = process_file(&path, &config, &theme) {
                             eprintln!("Reformat error: {}", e);
                         }
                     }
@@ -394,21 +636,16 @@ pub fn run_watch(path: String, args: cli::CliArgs) -> Result<(), JsonfizzError>
     Ok(())
 }
 
-fn process_file(path: &str, config: &crate::config::Config) -> Result<(), JsonfizzError> {
-    let input = std::fs::read_to_string(path)?;
-    let value: serde_json::Value = parse_input(&input, &config.input_format)?;
+fn process_file(path: &str, config: &crate::config::Config, theme: &crate::theme::Theme) -> Result<(), JsonfizzError> {
+    let input = read_file(path)?;
+    let (value, marks) = parse_document(&input, path, config, theme)?;
     let value = apply_get(&value, &config.get)?;
+    let marks = rebase_marks(marks, &config.get)?;
     validate_schema(&value, config)?;
-    let use_colors = match config.color {
-        Some(cli::ColorChoice::Always) => true,
-        Some(cli::ColorChoice::Never) => false,
-        Some(cli::ColorChoice::Auto) | None => atty::is(atty::Stream::Stdout),
-    };
-    let theme = crate::theme::Theme::new(&config.theme, config.raw || !use_colors)?;
-    let output = format_output(&value, config, &theme)?;
+    let output = format_output_marked(&value, config, theme, &marks)?;
     println!("--- file updated ---");
-    println!("{}", output);
-    println!("");
+    print!("{}", finish_output(&output, &input, config));
+    println!();
     Ok(())
 }
 
@@ -430,13 +667,34 @@ mod tests {
             compact: false,
             max_depth: None,
             max_string_length: None,
+            max_items: None,
             get: None,
             theme: "mono".to_string(),
+            theme_light: None,
+            theme_dark: None,
+            background: None,
             raw: false,
             format: "yaml".to_string(),
             input_format: "json".to_string(),
             color: None,
             schema: None,
+            lenient: false,
+            annotate: false,
+            line_numbers: false,
+            paging: None,
+            line_paths: false,
+            width: None,
+            indent_style: crate::cli::IndentStyle::Space,
+            newline: crate::cli::NewlineStyle::Lf,
+            final_newline: true,
+            key_separator: ".".to_string(),
+            value_separator: "=".to_string(),
+            columns: None,
+            delimiter: ",".to_string(),
+            quote: "\"".to_string(),
+            header: true,
+            column_types: None,
+            themes: Default::default(),
         };
         let theme = Theme::new("mono", false).unwrap();
         let result = format_output(&value, &config, &theme).unwrap();
@@ -453,13 +711,34 @@ mod tests {
             compact: false,
             max_depth: None,
             max_string_length: None,
+            max_items: None,
             get: None,
             theme: "mono".to_string(),
+            theme_light: None,
+            theme_dark: None,
+            background: None,
             raw: false,
             format: "toml".to_string(),
             input_format: "json".to_string(),
             color: None,
             schema: None,
+            lenient: false,
+            annotate: false,
+            line_numbers: false,
+            paging: None,
+            line_paths: false,
+            width: None,
+            indent_style: crate::cli::IndentStyle::Space,
+            newline: crate::cli::NewlineStyle::Lf,
+            final_newline: true,
+            key_separator: ".".to_string(),
+            value_separator: "=".to_string(),
+            columns: None,
+            delimiter: ",".to_string(),
+            quote: "\"".to_string(),
+            header: true,
+            column_types: None,
+            themes: Default::default(),
         };
         let theme = Theme::new("mono", false).unwrap();
         let result = format_output(&value, &config, &theme).unwrap();
@@ -471,7 +750,7 @@ mod tests {
     fn test_parse_toml_input() {
         let toml_input = r#"name = "test"
 version = 1.0"#;
-        let value = parse_input(toml_input, "toml").unwrap();
+        let value = parse_input(toml_input, &Config { input_format: "toml".to_string(), ..Default::default() }).unwrap();
         assert_eq!(value["name"], "test");
         assert_eq!(value["version"], 1.0);
     }
@@ -480,7 +759,7 @@ version = 1.0"#;
     fn test_parse_yaml_input() {
         let yaml_input = r#"name: test
 version: 1.0"#;
-        let value = parse_input(yaml_input, "yaml").unwrap();
+        let value = parse_input(yaml_input, &Config { input_format: "yaml".to_string(), ..Default::default() }).unwrap();
         assert_eq!(value["name"], "test");
         assert_eq!(value["version"], 1.0);
     }
@@ -497,13 +776,34 @@ version: 1.0"#;
             compact: false,
             max_depth: None,
             max_string_length: None,
+            max_items: None,
             get: None,
             theme: "mono".to_string(),
+            theme_light: None,
+            theme_dark: None,
+            background: None,
             raw: false,
             format: "csv".to_string(),
             input_format: "json".to_string(),
             color: None,
             schema: None,
+            lenient: false,
+            annotate: false,
+            line_numbers: false,
+            paging: None,
+            line_paths: false,
+            width: None,
+            indent_style: crate::cli::IndentStyle::Space,
+            newline: crate::cli::NewlineStyle::Lf,
+            final_newline: true,
+            key_separator: ".".to_string(),
+            value_separator: "=".to_string(),
+            columns: None,
+            delimiter: ",".to_string(),
+            quote: "\"".to_string(),
+            header: true,
+            column_types: None,
+            themes: Default::default(),
         };
         let theme = Theme::new("mono", false).unwrap();
         let result = format_output(&value, &config, &theme).unwrap();
@@ -512,6 +812,29 @@ version: 1.0"#;
         assert!(result.contains("25,LA,Bob"));
     }
 
+    #[test]
+    fn test_csv_nested_columns_and_delimiter() {
+        let value = json!([
+            {"id": 1, "user": {"name": "Ann", "address": {"city": "Oslo"}}, "tags": ["a", "b"]},
+            {"id": 2, "user": {"name": "Bo, Jr."}, "extra": null}
+        ]);
+        let theme = Theme::new("mono", false).unwrap();
+        let config = Config { format: "csv".to_string(), ..Default::default() };
+        assert_eq!(format_output(&value, &config, &theme).unwrap(), "id,tags,user.address.city,user.name,extra\n1,\"[\"\"a\"\",\"\"b\"\"]\",Oslo,Ann,\n2,,,\"Bo, Jr.\",\n");
+
+        let columns = Config { columns: Some("user.name, id,tags[1],missing".to_string()), delimiter: "tab".to_string(), ..config.clone() };
+        assert_eq!(format_output(&value, &columns, &theme).unwrap(), "user.name\tid\ttags[1]\tmissing\nAnn\t1\tb\t\nBo, Jr.\t2\t\t\n");
+        let semicolon = Config { columns: Some("id".to_string()), delimiter: ";".to_string(), ..config.clone() };
+        assert_eq!(format_output(&value, &semicolon, &theme).unwrap(), "id\n1\n2\n");
+        assert_eq!(format_output(&json!([{}, {"a": 1}]), &config, &theme).unwrap(), "a\n\"\"\n1\n");
+        let bad = Config { delimiter: "::".to_string(), ..config.clone() };
+        assert!(matches!(format_output(&value, &bad, &theme), Err(JsonfizzError::Config(_))));
+
+        let mixed = json!([{"a": 1}, "x", {"a": 2}, [1], null]);
+        let err = format_output(&mixed, &config, &theme).unwrap_err().to_string();
+        assert!(err.contains("3 of 5 are not: [1] (string), [3] (array), [4] (null)"), "{}", err);
+    }
+
     #[test]
     fn test_format_unsupported() {
         let value = json!({"test": "value"});
@@ -528,21 +851,63 @@ version: 1.0"#;
     #[test]
     fn test_parse_csv_input() {
         let csv_input = "name,age\nAlice,30\nBob,25\n";
-        let value = parse_input(csv_input, "csv").unwrap();
+        let value = parse_input(csv_input, &Config { input_format: "csv".to_string(), ..Default::default() }).unwrap();
         assert_eq!(value.as_array().unwrap().len(), 2);
         assert_eq!(value[0]["name"], "Alice");
-        assert_eq!(value[1]["age"], "25");
+        assert_eq!(value[1]["age"], 25);
     }
 
     #[test]
     fn test_parse_error_contains_location() {
-        let result = parse_input("{\"name\":}", "json");
+        let result = parse_input("{\"name\":}", &Config::default());
         assert!(result.is_err());
         let msg = result.unwrap_err().to_string();
         assert!(msg.contains("line"));
         assert!(msg.contains("column"));
     }
 
+    #[test]
+    fn test_parse_error_with_source() {
+        let input = "{\n  \"name\": \"test\",\n}\n";
+        let theme = Theme::new("mono", true).unwrap();
+        let err = parse_input(input, &Config::default()).unwrap_err().with_source("data.json", input, &theme);
+        let msg = err.to_string();
+        assert!(msg.starts_with("JSON parse error in data.json at line 3, column 1"));
+        assert!(msg.contains(" --> data.json:3:1"));
+        assert!(msg.contains("3 | }\n  | ^"));
+        assert!(msg.contains("hint: remove the trailing comma"));
+    }
+
+    #[test]
+    fn test_json_parse_error_column() {
+        // serde_json counts columns from 1, so the column is the offending character itself.
+        for (input, expected) in [("[1 2]", (1, 4)), ("{\n  \"a\" 1\n}", (2, 7))] {
+            match parse_input(input, &Config::default()).unwrap_err() {
+                JsonfizzError::Parse { line, column, .. } => assert_eq!((line, column), (Some(expected.0), Some(expected.1)), "{:?}", input),
+                other => panic!("unexpected error {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_document_lenient() {
+        let config = Config {
+            lenient: true,
+            get: Some("items".to_string()),
+            ..Default::default()
+        };
+        let theme = Theme::new("mono", true).unwrap();
+        let (value, marks) = parse_document("{\"items\": [1, 2", "<stdin>", &config, &theme).unwrap();
+        assert_eq!(value, json!({"items": [1, 2]}));
+        let value = apply_get(&value, &config.get).unwrap();
+        let marks = rebase_marks(marks, &config.get).unwrap();
+        assert_eq!(value, json!([1, 2]));
+        assert_eq!(marks, vec![Vec::new()]);
+
+        let strict = Config::default();
+        assert!(parse_document("{\"items\": [1, 2", "<stdin>", &strict, &theme).is_err());
+    }
+
     #[test]
     fn test_schema_validation_success() {
         let schema = json!({
@@ -554,7 +919,7 @@ version: 1.0"#;
             }
         });
         let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, "{}", schema.to_string()).unwrap();
+        writeln!(file, "{}", schema).unwrap();
 
         let config = Config {
             schema: Some(file.path().to_string_lossy().to_string()),
@@ -574,7 +939,7 @@ version: 1.0"#;
             }
         });
         let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, "{}", schema.to_string()).unwrap();
+        writeln!(file, "{}", schema).unwrap();
 
         let config = Config {
             schema: Some(file.path().to_string_lossy().to_string()),
@@ -586,5 +951,34 @@ version: 1.0"#;
         let err = result.unwrap_err();
         let msg = err.to_string();
         assert!(msg.contains("Schema validation failed") || msg.contains("/age"));
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_finish_output_ends_serialized_formats_once() {
+        let value = json!([{"a": 1}]);
+        let theme = Theme::new("mono", false).unwrap();
+        for (format, expected) in [("yaml", "---\n- a: 1\n"), ("csv", "a\n1\n")] {
+            let config = Config { format: format.to_string(), ..Default::default() };
+            let output = format_output(&value, &config, &theme).unwrap();
+            assert_eq!(finish_output(&output, "", &config), expected);
+        }
+        let toml = Config { format: "toml".to_string(), ..Default::default() };
+        let output = format_output(&json!({"a": 1}), &toml, &theme).unwrap();
+        assert_eq!(finish_output(&output, "", &toml), "a = 1\n");
+    }
+
+    #[test]
+    fn test_finish_output_newlines() {
+        let lf = Config::default();
+        assert_eq!(finish_output("{\n  \"a\": 1\n}", "{\"a\":1}", &lf), "{\n  \"a\": 1\n}\n");
+        assert_eq!(finish_output("a: 1\n", "", &lf), "a: 1\n");
+
+        let crlf = Config { newline: cli::NewlineStyle::Crlf, ..Default::default() };
+        assert_eq!(finish_output("{\n}", "", &crlf), "{\r\n}\r\n");
+
+        let auto = Config { newline: cli::NewlineStyle::Auto, final_newline: false, ..Default::default() };
+        assert_eq!(finish_output("{\n}\n", "{\r\n}\r\n", &auto), "{\r\n}");
+        assert_eq!(finish_output("{\n}\n", "{\n}\n", &auto), "{\n}");
     }
 }
@@ -1,9 +1,9 @@
-use clap::{Parser, CommandFactory};
+use clap::CommandFactory;
 use clap_complete::generate;
-use jsonfizz::cli::CliArgs;
+use jsonfizz::cli::{CliArgs, ErrorFormat};
 
 fn main() {
-    let args = CliArgs::parse();
+    let args = CliArgs::parse_tracked();
 
     // Handle completion generation
     if let Some(shell) = args.generate_completion {
@@ -19,8 +19,21 @@ fn main() {
         return;
     }
 
-    if let Err(e) = jsonfizz::run(args, std::io::stdout()) {
-        eprintln!("error: {}", e);
+    let error_format = args.error_format;
+    let result = if let Some(jsonfizz::cli::Command::Stats(stats_args)) = &args.command {
+        jsonfizz::stats(&args, stats_args, std::io::stdout())
+    } else if args.print_config {
+        jsonfizz::print_config(&args, std::io::stdout())
+    } else if args.list_themes {
+        jsonfizz::list_themes(&args, std::io::stdout())
+    } else {
+        jsonfizz::run(args, std::io::stdout())
+    };
+    if let Err(e) = result {
+        match error_format {
+            ErrorFormat::Human => eprintln!("error: {}", e),
+            ErrorFormat::Json => eprintln!("{}", e.to_json()),
+        }
         std::process::exit(e.exit_code());
     }
 }
\ No newline at end of file
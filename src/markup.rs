@@ -0,0 +1,274 @@
+use std::fmt::Write as _;
+use crate::theme::{Background, TermColor, TextStyle};
+
+const FONT_FAMILY: &str = "ui-monospace, SFMono-Regular, Menlo, Consolas, monospace";
+const FONT_SIZE: f64 = 13.0;
+const LINE_HEIGHT: f64 = 18.0;
+/// Advance of one monospace character at `FONT_SIZE`, for sizing SVG output.
+const CHAR_WIDTH: f64 = 7.8;
+const PADDING: f64 = 12.0;
+
+/// Output formats rendered as markup rather than terminal text.
+pub fn is_markup_format(format: &str) -> bool {
+    matches!(format, "html" | "svg")
+}
+
+/// CSS for one style; SVG text is colored with `fill` and has no background.
+fn css(style: &TextStyle, svg: bool) -> String {
+    let mut rules = Vec::new();
+    if let Some((r, g, b)) = style.fg.map(TermColor::rgb) {
+        rules.push(format!("{}: #{:02x}{:02x}{:02x}", if svg { "fill" } else { "color" }, r, g, b));
+    }
+    if let (Some((r, g, b)), false) = (style.bg.map(TermColor::rgb), svg) {
+        rules.push(format!("background-color: #{:02x}{:02x}{:02x}", r, g, b));
+    }
+    if style.bold {
+        rules.push("font-weight: bold".to_string());
+    }
+    if style.dim {
+        rules.push(format!("{}: 0.6", if svg { "fill-opacity" } else { "opacity" }));
+    }
+    if style.italic {
+        rules.push("font-style: italic".to_string());
+    }
+    if style.underline {
+        rules.push("text-decoration: underline".to_string());
+    }
+    rules.join("; ")
+}
+
+/// Highlighted output split into lines of styled runs, with each distinct
+/// style given a CSS class.
+struct Document {
+    lines: Vec<Vec<(usize, String)>>,
+    styles: Vec<TextStyle>,
+}
+
+impl Document {
+    /// Split terminal output into styled runs. Style index 0 is unstyled text.
+    fn parse(ansi: &str) -> Self {
+        let mut styles = vec![TextStyle::default()];
+        let mut current = TextStyle::default();
+        let mut lines = Vec::new();
+        for line in ansi.split('\n') {
+            let mut runs: Vec<(usize, String)> = Vec::new();
+            let mut rest = line;
+            while !rest.is_empty() {
+                if let Some(seq) = rest.strip_prefix("\x1b[") {
+                    let end = seq.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(seq.len());
+                    if seq[end..].starts_with('m') {
+                        current.apply_sgr(&seq[..end]);
+                    }
+                    rest = seq.get(end + 1..).unwrap_or("");
+                    continue;
+                }
+                let end = rest.find('\x1b').unwrap_or(rest.len()).max(1);
+                let index = match styles.iter().position(|s| *s == current) {
+                    Some(index) => index,
+                    None => {
+                        styles.push(current.clone());
+                        styles.len() - 1
+                    }
+                };
+                match runs.last_mut() {
+                    Some((last, text)) if *last == index => text.push_str(&rest[..end]),
+                    _ => runs.push((index, rest[..end].to_string())),
+                }
+                rest = &rest[end..];
+            }
+            lines.push(runs);
+        }
+        if lines.len() > 1 && lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        Document { lines, styles }
+    }
+
+    fn plain(&self, line: usize) -> String {
+        self.lines[line].iter().map(|(_, text)| text.as_str()).collect()
+    }
+
+    fn stylesheet(&self, prefix: &str, svg: bool) -> String {
+        let mut sheet = String::new();
+        for (index, style) in self.styles.iter().enumerate().skip(1) {
+            let _ = writeln!(sheet, "{prefix} .s{index} {{ {} }}", css(style, svg));
+        }
+        sheet
+    }
+
+    fn runs_markup(&self, line: usize) -> String {
+        self.lines[line]
+            .iter()
+            .map(|(index, text)| match index {
+                0 => escape(text),
+                _ => format!("<span class=\"s{}\">{}</span>", index, escape(text)),
+            })
+            .collect()
+    }
+
+    /// Pair each line that opens an object or array with the line that
+    /// closes it, matching brackets at the same indentation.
+    fn containers(&self) -> Vec<Option<usize>> {
+        let mut closes = vec![None; self.lines.len()];
+        let mut open: Vec<(usize, usize, char)> = Vec::new();
+        for i in 0..self.lines.len() {
+            let plain = self.plain(i);
+            let indent = plain.len() - plain.trim_start().len();
+            let trimmed = plain.trim();
+            if let Some(closer) = trimmed.chars().next().filter(|c| matches!(c, '}' | ']')) {
+                let opener = if closer == '}' { '{' } else { '[' };
+                if let Some(pos) = open.iter().rposition(|(_, ind, c)| *ind == indent && *c == opener) {
+                    closes[open[pos].0] = Some(i);
+                    open.truncate(pos);
+                }
+            }
+            if let Some(opener) = trimmed.chars().last().filter(|c| matches!(c, '{' | '[')) {
+                open.push((i, indent, opener));
+            }
+        }
+        closes
+    }
+}
+
+fn page_colors(background: Background) -> (&'static str, &'static str) {
+    match background {
+        Background::Dark => ("#1e1e1e", "#d4d4d4"),
+        Background::Light => ("#ffffff", "#1e1e1e"),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Render highlighted terminal output as a self-contained HTML page. Objects
+/// and arrays become collapsible `<details>` blocks.
+pub fn to_html(ansi: &str, line_numbers: bool, background: Background) -> String {
+    let doc = Document::parse(ansi);
+    let (bg, fg) = page_colors(background);
+    let width = doc.lines.len().to_string().len();
+    let closes = doc.containers();
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>jsonfizz</title>\n<style>\n");
+    let _ = writeln!(
+        out,
+        ".jsonfizz {{ background: {bg}; color: {fg}; font-family: {FONT_FAMILY}; font-size: {FONT_SIZE}px; line-height: {LINE_HEIGHT}px; padding: {PADDING}px 0; border-radius: 6px; overflow-x: auto; }}"
+    );
+    let _ = writeln!(out, ".jsonfizz .line {{ white-space: pre; padding: 0 {PADDING}px; }}");
+    let _ = writeln!(out, ".jsonfizz .ln {{ display: inline-block; min-width: {width}ch; margin-right: 2ch; text-align: right; opacity: 0.5; user-select: none; }}");
+    out.push_str(".jsonfizz summary { list-style: none; cursor: pointer; }\n");
+    out.push_str(".jsonfizz summary::-webkit-details-marker { display: none; }\n");
+    out.push_str(".jsonfizz details:not([open]) > summary::after { content: \"…\" attr(data-close); opacity: 0.6; }\n");
+    out.push_str(&doc.stylesheet(".jsonfizz", false));
+    out.push_str("</style>\n</head>\n<body>\n<div class=\"jsonfizz\">\n");
+
+    for (i, close) in closes.iter().enumerate() {
+        let number = if line_numbers { format!("<span class=\"ln\">{}</span>", i + 1) } else { String::new() };
+        let content = doc.runs_markup(i);
+        match close {
+            Some(_) => {
+                let closer = if doc.plain(i).trim_end().ends_with('{') { "}" } else { "]" };
+                let _ = writeln!(out, "<details open><summary class=\"line\" data-close=\"{closer}\">{number}{content}</summary>");
+            }
+            None => {
+                let _ = writeln!(out, "<div class=\"line\">{number}{content}</div>");
+            }
+        }
+        for _ in closes.iter().filter(|c| **c == Some(i)) {
+            out.push_str("</details>\n");
+        }
+    }
+    out.push_str("</div>\n</body>\n</html>");
+    out
+}
+
+/// Render highlighted terminal output as a standalone SVG image.
+pub fn to_svg(ansi: &str, line_numbers: bool, background: Background) -> String {
+    let doc = Document::parse(ansi);
+    let (bg, fg) = page_colors(background);
+    let width = doc.lines.len().to_string().len();
+    let gutter = if line_numbers { width + 2 } else { 0 };
+    let columns = (0..doc.lines.len()).map(|i| doc.plain(i).chars().count()).max().unwrap_or(0) + gutter;
+    let image_width = (PADDING * 2.0 + columns as f64 * CHAR_WIDTH).ceil();
+    let image_height = (PADDING * 2.0 + doc.lines.len() as f64 * LINE_HEIGHT).ceil();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{image_width}\" height=\"{image_height}\" viewBox=\"0 0 {image_width} {image_height}\">"
+    );
+    out.push_str("<style>\n");
+    let _ = writeln!(out, ".jsonfizz {{ font-family: {FONT_FAMILY}; font-size: {FONT_SIZE}px; fill: {fg}; white-space: pre; }}");
+    out.push_str(".jsonfizz .ln { fill-opacity: 0.5; }\n");
+    out.push_str(&doc.stylesheet(".jsonfizz", true));
+    out.push_str("</style>\n");
+    let _ = writeln!(out, "<rect width=\"100%\" height=\"100%\" rx=\"6\" fill=\"{bg}\"/>");
+    out.push_str("<g class=\"jsonfizz\">\n");
+    for i in 0..doc.lines.len() {
+        let y = PADDING + (i as f64 + 0.75) * LINE_HEIGHT;
+        let number = if line_numbers { format!("<tspan class=\"ln\">{:>width$}  </tspan>", i + 1, width = width) } else { String::new() };
+        let content: String = doc.lines[i]
+            .iter()
+            .map(|(index, text)| match index {
+                0 => format!("<tspan>{}</tspan>", escape(text)),
+                _ => format!("<tspan class=\"s{}\">{}</tspan>", index, escape(text)),
+            })
+            .collect();
+        let _ = writeln!(out, "<text x=\"{PADDING}\" y=\"{y}\" xml:space=\"preserve\">{number}{content}</text>");
+    }
+    out.push_str("</g>\n</svg>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\x1b[97m{\x1b[0m\n  \"\x1b[33ma<b\x1b[0m\": \x1b[97m[\x1b[0m\n    \x1b[1;38;2;255;136;0m1\x1b[0m\n  \x1b[97m]\x1b[0m\n\x1b[97m}\x1b[0m";
+
+    #[test]
+    fn test_parse_sgr_runs() {
+        let doc = Document::parse(SAMPLE);
+        assert_eq!(doc.lines.len(), 5);
+        assert_eq!(doc.plain(1), "  \"a<b\": [");
+        assert_eq!(doc.styles[1].fg.map(TermColor::rgb), Some((255, 255, 255)));
+        let orange = doc.styles.iter().find(|s| s.bold).unwrap();
+        assert_eq!(orange.fg, Some(TermColor::Rgb(255, 136, 0)));
+        assert_eq!(doc.containers(), vec![Some(4), Some(3), None, None, None]);
+    }
+
+    #[test]
+    fn test_html_output() {
+        let html = to_html(SAMPLE, true, Background::Dark);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(".jsonfizz .s1 { color: #ffffff }"));
+        assert!(html.contains("font-weight: bold"));
+        assert!(html.contains("a&lt;b"));
+        assert!(!html.contains('\x1b'));
+        assert_eq!(html.matches("<details open>").count(), 2);
+        assert_eq!(html.matches("</details>").count(), 2);
+        assert!(html.contains("<span class=\"ln\">3</span>"));
+        assert!(html.contains("data-close=\"]\""));
+        let without_numbers = to_html(SAMPLE, false, Background::Light);
+        assert!(!without_numbers.contains("class=\"ln\""));
+        assert!(without_numbers.contains("background: #ffffff"));
+    }
+
+    #[test]
+    fn test_svg_output() {
+        let svg = to_svg(SAMPLE, true, Background::Dark);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert_eq!(svg.matches("<text ").count(), 5);
+        assert!(svg.contains(".jsonfizz .s1 { fill: #ffffff }"));
+        assert!(svg.contains("<tspan class=\"ln\">1  </tspan>"));
+        assert!(svg.contains("a&lt;b"));
+    }
+
+    #[test]
+    fn test_plain_text_without_styles() {
+        let html = to_html("{\n  \"a\": 1\n}\n", false, Background::Dark);
+        assert!(html.contains("<div class=\"line\">  \"a\": 1</div>"));
+        assert_eq!(html.matches("<details open>").count(), 1);
+    }
+}
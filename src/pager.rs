@@ -0,0 +1,110 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+const DEFAULT_PAGER: &str = "less -R";
+
+/// The pager to run: `$PAGER`, or `less -R` when it is unset. An empty
+/// `$PAGER` turns paging off. `less` is always given `-R` so colors survive.
+pub fn pager_command(pager: Option<&str>) -> Option<(String, Vec<String>)> {
+    let mut words = pager.unwrap_or(DEFAULT_PAGER).split_whitespace().map(str::to_string);
+    let program = words.next()?;
+    let mut args: Vec<String> = words.collect();
+    let is_less = std::path::Path::new(&program).file_stem().is_some_and(|stem| stem == "less");
+    let keeps_colors = args.iter().any(|arg| arg == "-R" || arg == "-r" || arg == "--RAW-CONTROL-CHARS");
+    if is_less && !keeps_colors {
+        args.push("-R".to_string());
+    }
+    Some((program, args))
+}
+
+/// Whether `output` fits on a terminal of `rows` by `columns` without
+/// scrolling, counting wrapped lines. Escape sequences take no space.
+pub fn fits(output: &[u8], rows: usize, columns: usize) -> bool {
+    let text = String::from_utf8_lossy(output);
+    let columns = columns.max(1);
+    let mut used = 0;
+    for line in text.lines() {
+        used += visible_width(line).div_ceil(columns).max(1);
+        if used >= rows {
+            return false;
+        }
+    }
+    true
+}
+
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a CSI sequence up to and including its final letter.
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Whether the output is taller than the terminal on stdout.
+pub fn taller_than_screen(output: &[u8]) -> bool {
+    match crossterm::terminal::size() {
+        Ok((columns, rows)) => !fits(output, rows as usize, columns as usize),
+        Err(_) => false,
+    }
+}
+
+/// Show `output` in the pager. Returns `false` without writing anything when
+/// no pager is configured or it cannot be started, so the caller can print
+/// the output itself.
+pub fn page(output: &[u8]) -> io::Result<bool> {
+    let pager = std::env::var("PAGER").ok();
+    let Some((program, args)) = pager_command(pager.as_deref()) else {
+        return Ok(false);
+    };
+    let mut child = match Command::new(&program).args(&args).stdin(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(_) => return Ok(false),
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when the user quits before the end.
+        match stdin.write_all(output) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(program: &str, args: &[&str]) -> Option<(String, Vec<String>)> {
+        Some((program.to_string(), args.iter().map(|a| a.to_string()).collect()))
+    }
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(None), command("less", &["-R"]));
+        assert_eq!(pager_command(Some("less")), command("less", &["-R"]));
+        assert_eq!(pager_command(Some("/usr/bin/less -S")), command("/usr/bin/less", &["-S", "-R"]));
+        assert_eq!(pager_command(Some("less -r")), command("less", &["-r"]));
+        assert_eq!(pager_command(Some("bat --plain")), command("bat", &["--plain"]));
+        assert_eq!(pager_command(Some("")), None);
+    }
+
+    #[test]
+    fn test_fits_screen() {
+        assert!(fits(b"{\n  \"a\": 1\n}\n", 4, 80));
+        assert!(!fits(b"{\n  \"a\": 1\n}\n", 3, 80));
+        let colored = "\x1b[34m\"key\"\x1b[0m: 1\n".repeat(2);
+        assert!(fits(colored.as_bytes(), 3, 10));
+        assert!(!fits("x".repeat(25).as_bytes(), 3, 10));
+    }
+}
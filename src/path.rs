@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PathSegment {
     Key(String),
     Index(usize),
@@ -6,22 +6,55 @@ pub enum PathSegment {
 
 pub type JsonPath = Vec<PathSegment>;
 
+/// Parse a path like `data.items[0].id`. Indexes may be chained (`grid[1][2]`)
+/// or start the path (`[0].id`), and an empty path is the root.
 pub fn parse_path(path: &str) -> Result<JsonPath, crate::error::JsonfizzError> {
+    parse_path_with(path, ".")
+}
+
+/// Like [`parse_path`], with keys joined by `separator` instead of `.`.
+pub fn parse_path_with(path: &str, separator: &str) -> Result<JsonPath, crate::error::JsonfizzError> {
     let mut segments = Vec::new();
-    for part in path.split('.') {
-        if let Some(pos) = part.rfind('[') {
-            let key = part[..pos].to_string();
-            let index_str = &part[pos + 1..part.len() - 1];
+    if path.is_empty() {
+        return Ok(segments);
+    }
+    for part in path.split(separator) {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !key.is_empty() || rest.is_empty() {
+            segments.push(PathSegment::Key(key.to_string()));
+        }
+        while !rest.is_empty() {
+            let close = rest.find(']').filter(|_| rest.starts_with('['));
+            let index_str = close.map_or(rest, |close| &rest[1..close]);
             let index: usize = index_str.parse().map_err(|_| crate::error::JsonfizzError::Path(format!("Invalid index '{}' in path '{}'", index_str, path)))?;
-            segments.push(PathSegment::Key(key));
             segments.push(PathSegment::Index(index));
-        } else {
-            segments.push(PathSegment::Key(part.to_string()));
+            rest = &rest[close.map_or(rest.len(), |close| close + 1)..];
         }
     }
     Ok(segments)
 }
 
+pub fn format_path(path: &JsonPath) -> String {
+    format_path_with(path, ".")
+}
+
+/// Like [`format_path`], with keys joined by `separator` instead of `.`.
+pub fn format_path_with(path: &JsonPath, separator: &str) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => {
+                if !out.is_empty() {
+                    out.push_str(separator);
+                }
+                out.push_str(key);
+            }
+            PathSegment::Index(index) => out.push_str(&format!("[{}]", index)),
+        }
+    }
+    out
+}
+
 pub fn resolve(value: &serde_json::Value, path: &JsonPath) -> Result<serde_json::Value, crate::error::JsonfizzError> {
     let mut current = value.clone();
     for segment in path {
@@ -68,6 +101,37 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_parse_path_indexes() {
+        assert_eq!(parse_path("[0].id").unwrap(), vec![PathSegment::Index(0), PathSegment::Key("id".to_string())]);
+        assert_eq!(parse_path("grid[1][2]").unwrap(), vec![
+            PathSegment::Key("grid".to_string()),
+            PathSegment::Index(1),
+            PathSegment::Index(2),
+        ]);
+        assert_eq!(parse_path("").unwrap(), vec![]);
+        assert!(parse_path("items[x]").is_err());
+        assert!(parse_path("items[1").is_err());
+        assert!(parse_path("items[").is_err());
+        assert!(parse_path("items[1]x").is_err());
+        assert_eq!(parse_path_with("a__b[0][1]", "__").unwrap(), vec![
+            PathSegment::Key("a".to_string()),
+            PathSegment::Key("b".to_string()),
+            PathSegment::Index(0),
+            PathSegment::Index(1),
+        ]);
+    }
+
+    #[test]
+    fn test_format_path() {
+        let path = parse_path("data.items[0].id").unwrap();
+        assert_eq!(format_path(&path), "data.items[0].id");
+        assert_eq!(format_path(&vec![]), "");
+        let env = parse_path_with("data__items[0]__id", "__").unwrap();
+        assert_eq!(env, path);
+        assert_eq!(format_path_with(&env, "__"), "data__items[0]__id");
+    }
+
     #[test]
     fn test_resolve() {
         let value = json!({
@@ -0,0 +1,428 @@
+use serde_json::{Map, Value};
+use crate::error::JsonfizzError;
+use crate::path::{JsonPath, PathSegment};
+
+const MAX_DEPTH: usize = 512;
+
+/// A single fix applied while recovering a broken document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repair {
+    pub line: usize,
+    pub column: usize,
+    pub path: JsonPath,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Recovered {
+    pub value: Value,
+    pub repairs: Vec<Repair>,
+}
+
+/// Parse JSON tolerantly, repairing what can be repaired instead of failing.
+///
+/// Unterminated strings, arrays and objects are closed at EOF, truncated
+/// literals and numbers are completed, missing values become `null`, and
+/// stray or missing commas are fixed up. Each fix is recorded as a [`Repair`].
+pub fn parse_lenient(input: &str) -> Result<Recovered, JsonfizzError> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        counted: 0,
+        line: 1,
+        column: 1,
+        repairs: Vec::new(),
+        path: Vec::new(),
+    };
+    parser.skip_ws();
+    if parser.peek().is_none() {
+        return Err(JsonfizzError::parse_error("JSON", "no JSON value found", None, None));
+    }
+    let value = parser.parse_value(0);
+    parser.skip_ws();
+    if parser.peek().is_some() {
+        parser.repair("ignored trailing content after the document");
+    }
+    Ok(Recovered {
+        value,
+        repairs: parser.repairs,
+    })
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// `line` and `column` are those of `chars[counted]`. The parser only
+    /// moves forward, so each repair counts on from the previous one.
+    counted: usize,
+    line: usize,
+    column: usize,
+    repairs: Vec<Repair>,
+    path: JsonPath,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn repair(&mut self, message: impl Into<String>) {
+        let end = self.pos.min(self.chars.len());
+        for &c in &self.chars[self.counted..end] {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.counted = end;
+        self.repairs.push(Repair {
+            line: self.line,
+            column: self.column,
+            path: self.path.clone(),
+            message: message.into(),
+        });
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Value {
+        self.skip_ws();
+        if depth > MAX_DEPTH {
+            self.repair(format!("dropped content nested deeper than {} levels", MAX_DEPTH));
+            self.pos = self.chars.len();
+            return Value::Null;
+        }
+        match self.peek() {
+            None => {
+                self.repair("filled missing value with null");
+                Value::Null
+            }
+            Some('{') => self.parse_object(depth),
+            Some('[') => self.parse_array(depth),
+            Some('"') => Value::String(self.parse_string()),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.parse_number(),
+            Some(':') => {
+                // One repair for a whole run of colons, which is then
+                // skipped here rather than one recursive call per colon.
+                self.repair("skipped unexpected character `:`");
+                while self.peek() == Some(':') {
+                    self.pos += 1;
+                    self.skip_ws();
+                }
+                self.parse_value(depth)
+            }
+            Some(',') | Some('}') | Some(']') => {
+                self.repair("filled missing value with null");
+                Value::Null
+            }
+            Some(_) => self.parse_literal(),
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Value {
+        self.pos += 1;
+        let mut map = Map::new();
+        let mut need_comma = false;
+        let mut after_comma = false;
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                None => {
+                    self.repair("closed unterminated object");
+                    break;
+                }
+                Some('}') => {
+                    if after_comma {
+                        self.repair("removed trailing comma");
+                    }
+                    self.pos += 1;
+                    break;
+                }
+                Some(']') => {
+                    self.repair("closed object before mismatched `]`");
+                    break;
+                }
+                Some(',') => {
+                    if !need_comma {
+                        self.repair("removed extra comma");
+                    }
+                    self.pos += 1;
+                    need_comma = false;
+                    after_comma = true;
+                }
+                Some(c) => {
+                    let key = if c == '"' {
+                        self.parse_string()
+                    } else if c.is_alphanumeric() || c == '_' || c == '$' {
+                        self.repair("quoted unquoted key");
+                        self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '-')
+                    } else {
+                        self.repair(format!("skipped unexpected character `{}`", c));
+                        self.pos += 1;
+                        continue;
+                    };
+                    if need_comma {
+                        self.repair("inserted missing comma");
+                    }
+                    self.skip_ws();
+                    match self.peek() {
+                        Some(':') => self.pos += 1,
+                        None => {}
+                        Some(_) => self.repair("inserted missing colon"),
+                    }
+                    self.path.push(PathSegment::Key(key.clone()));
+                    let value = self.parse_value(depth + 1);
+                    self.path.pop();
+                    map.insert(key, value);
+                    need_comma = true;
+                    after_comma = false;
+                }
+            }
+        }
+        Value::Object(map)
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Value {
+        self.pos += 1;
+        let mut items = Vec::new();
+        let mut need_comma = false;
+        let mut after_comma = false;
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                None => {
+                    self.repair("closed unterminated array");
+                    break;
+                }
+                Some(']') => {
+                    if after_comma {
+                        self.repair("removed trailing comma");
+                    }
+                    self.pos += 1;
+                    break;
+                }
+                Some('}') => {
+                    self.repair("closed array before mismatched `}`");
+                    break;
+                }
+                Some(',') => {
+                    if !need_comma {
+                        self.repair("removed extra comma");
+                    }
+                    self.pos += 1;
+                    need_comma = false;
+                    after_comma = true;
+                }
+                Some(_) => {
+                    if need_comma {
+                        self.repair("inserted missing comma");
+                    }
+                    self.path.push(PathSegment::Index(items.len()));
+                    let value = self.parse_value(depth + 1);
+                    self.path.pop();
+                    items.push(value);
+                    need_comma = true;
+                    after_comma = false;
+                }
+            }
+        }
+        Value::Array(items)
+    }
+
+    fn parse_string(&mut self) -> String {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => {
+                    self.repair("closed unterminated string");
+                    break;
+                }
+                Some('\n') => {
+                    self.repair("closed string at end of line");
+                    break;
+                }
+                Some('"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        None => {}
+                        Some('u') => {
+                            self.pos += 1;
+                            match self.parse_unicode_escape() {
+                                Some(c) => out.push(c),
+                                None => self.repair("dropped invalid unicode escape"),
+                            }
+                        }
+                        Some(c) => {
+                            self.pos += 1;
+                            out.push(match c {
+                                'n' => '\n',
+                                't' => '\t',
+                                'r' => '\r',
+                                'b' => '\u{8}',
+                                'f' => '\u{c}',
+                                '"' | '\\' | '/' => c,
+                                other => {
+                                    self.repair(format!("kept invalid escape `\\{}` literally", other));
+                                    other
+                                }
+                            });
+                        }
+                    }
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    out.push(c);
+                }
+            }
+        }
+        out
+    }
+
+    fn parse_unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if (0xD800..0xDC00).contains(&high) {
+            if self.chars.get(self.pos) == Some(&'\\') && self.chars.get(self.pos + 1) == Some(&'u') {
+                self.pos += 2;
+                let low = self.hex4()?;
+                return char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low.checked_sub(0xDC00)? & 0x3FF));
+            }
+            return None;
+        }
+        char::from_u32(high)
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits: String = self.chars.iter().skip(self.pos).take(4).take_while(|c| c.is_ascii_hexdigit()).collect();
+        self.pos += digits.len();
+        if digits.len() == 4 {
+            u32::from_str_radix(&digits, 16).ok()
+        } else {
+            None
+        }
+    }
+
+    fn parse_number(&mut self) -> Value {
+        let text = self.take_while(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'));
+        if let Ok(n) = serde_json::from_str::<serde_json::Number>(&text) {
+            return Value::Number(n);
+        }
+        let trimmed = text.trim_end_matches(|c: char| !c.is_ascii_digit());
+        match serde_json::from_str::<serde_json::Number>(trimmed) {
+            Ok(n) => {
+                self.repair(format!("completed truncated number `{}`", text));
+                Value::Number(n)
+            }
+            Err(_) => {
+                self.repair(format!("replaced invalid number `{}` with null", text));
+                Value::Null
+            }
+        }
+    }
+
+    fn parse_literal(&mut self) -> Value {
+        let word = self.take_while(|c| !c.is_whitespace() && !matches!(c, ',' | ':' | '{' | '}' | '[' | ']' | '"'));
+        for (literal, value) in [("true", Value::Bool(true)), ("false", Value::Bool(false)), ("null", Value::Null)] {
+            if word == literal {
+                return value;
+            }
+            if self.peek().is_none() && literal.starts_with(&word) {
+                self.repair(format!("completed truncated literal `{}`", word));
+                return value;
+            }
+        }
+        self.repair(format!("replaced invalid value `{}` with null", word));
+        Value::Null
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_valid_input_has_no_repairs() {
+        let recovered = parse_lenient(r#"{"a": [1, 2.5, "x\né"], "b": null, "c": true}"#).unwrap();
+        assert_eq!(recovered.value, json!({"a": [1, 2.5, "x\né"], "b": null, "c": true}));
+        assert!(recovered.repairs.is_empty());
+    }
+
+    #[test]
+    fn test_truncated_document() {
+        let recovered = parse_lenient("{\"users\": [{\"name\": \"Al").unwrap();
+        assert_eq!(recovered.value, json!({"users": [{"name": "Al"}]}));
+        let messages: Vec<&str> = recovered.repairs.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["closed unterminated string", "closed unterminated object", "closed unterminated array", "closed unterminated object"]);
+        assert_eq!(recovered.repairs[0].path, vec![
+            PathSegment::Key("users".to_string()),
+            PathSegment::Index(0),
+            PathSegment::Key("name".to_string()),
+        ]);
+        assert_eq!(recovered.repairs[0].line, 1);
+        assert_eq!(recovered.repairs[0].column, 24);
+    }
+
+    #[test]
+    fn test_truncated_scalars() {
+        assert_eq!(parse_lenient("[tr").unwrap().value, json!([true]));
+        assert_eq!(parse_lenient("[1.").unwrap().value, json!([1]));
+        assert_eq!(parse_lenient("{\"a\":").unwrap().value, json!({"a": null}));
+    }
+
+    #[test]
+    fn test_comma_repairs() {
+        let recovered = parse_lenient("{\"a\": 1 \"b\": [1,, 2,],}").unwrap();
+        assert_eq!(recovered.value, json!({"a": 1, "b": [1, 2]}));
+        assert_eq!(recovered.repairs.len(), 4);
+    }
+
+    #[test]
+    fn test_repair_positions_across_lines() {
+        let input = format!("[{}\n  1,,\n]", ",".repeat(100_000));
+        let recovered = parse_lenient(&input).unwrap();
+        assert_eq!(recovered.value, json!([1]));
+        let positions: Vec<(usize, usize)> = recovered.repairs.iter().map(|r| (r.line, r.column)).collect();
+        assert_eq!(positions.len(), 100_002);
+        assert_eq!(positions[..2], [(1, 2), (1, 3)]);
+        assert_eq!(positions[100_000..], [(2, 5), (3, 1)]);
+    }
+
+    #[test]
+    fn test_garbage_terminates() {
+        let recovered = parse_lenient("[1 : 'x' }{ ::").unwrap();
+        assert_eq!(recovered.value[0], json!(1));
+        assert!(!recovered.repairs.is_empty());
+    }
+
+    #[test]
+    fn test_colon_run_does_not_recurse() {
+        let input = format!("[{}", ":".repeat(200_000));
+        let recovered = parse_lenient(&input).unwrap();
+        assert_eq!(recovered.value, json!([null]));
+        assert_eq!(recovered.repairs[0].message, "skipped unexpected character `:`");
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert!(parse_lenient("  \n").is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use serde_json::Value;
+use crate::config::Config;
+use crate::theme::{colorize, Theme, TokenKind};
+
+/// The types observed at one place in a document. Arrays merge the shapes
+/// of all their items, so an array of similar objects collapses into one
+/// object shape.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Shape {
+    null: bool,
+    boolean: bool,
+    int: bool,
+    float: bool,
+    string: bool,
+    /// The merged shape of every item, when an array was seen here.
+    array: Option<Box<Shape>>,
+    /// The fields of the objects seen here and how many objects there were.
+    object: Option<(BTreeMap<String, Field>, usize)>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Field {
+    shape: Shape,
+    /// How many of the objects had this key.
+    seen: usize,
+}
+
+impl Shape {
+    /// The shape of `value`.
+    pub fn of(value: &Value) -> Self {
+        let mut shape = Shape::default();
+        shape.add(value);
+        shape
+    }
+
+    /// Merge one more observed `value` into this shape.
+    fn add(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_f64() => self.float = true,
+            Value::Number(_) => self.int = true,
+            Value::String(_) => self.string = true,
+            Value::Array(arr) => {
+                let items = self.array.get_or_insert_with(Default::default);
+                for v in arr {
+                    items.add(v);
+                }
+            }
+            Value::Object(map) => {
+                let (fields, count) = self.object.get_or_insert_with(Default::default);
+                *count += 1;
+                for (k, v) in map {
+                    let field = fields.entry(k.clone()).or_default();
+                    field.seen += 1;
+                    field.shape.add(v);
+                }
+            }
+        }
+    }
+
+    /// Whether nothing was seen here: the items of arrays that were always empty.
+    fn is_empty(&self) -> bool {
+        *self == Shape::default()
+    }
+
+    /// The shape as one line of `(text, kind)` tokens, such as
+    /// `{id: int, tags: [string]}`. `depth` picks the bracket color.
+    fn inline(&self, depth: usize) -> Vec<(String, TokenKind)> {
+        let mut alternatives: Vec<Vec<(String, TokenKind)>> = Vec::new();
+        let scalars = [
+            (self.boolean, "bool", TokenKind::Bool),
+            (self.int && !self.float, "int", TokenKind::Integer),
+            (self.float && !self.int, "float", TokenKind::Float),
+            (self.int && self.float, "number", TokenKind::Number),
+            (self.string, "string", TokenKind::String),
+        ];
+        for (seen, name, kind) in scalars {
+            if seen {
+                alternatives.push(vec![(name.to_string(), kind)]);
+            }
+        }
+        if let Some(items) = &self.array {
+            let mut tokens = vec![("[".to_string(), TokenKind::Bracket(depth))];
+            if !items.is_empty() {
+                tokens.extend(items.inline(depth + 1));
+            }
+            tokens.push(("]".to_string(), TokenKind::Bracket(depth)));
+            alternatives.push(tokens);
+        }
+        if let Some((fields, count)) = &self.object {
+            let mut tokens = vec![("{".to_string(), TokenKind::Bracket(depth))];
+            for (i, (key, field)) in fields.iter().enumerate() {
+                if i > 0 {
+                    tokens.push((", ".to_string(), TokenKind::Comma));
+                }
+                tokens.extend(field_label(key, field, *count));
+                tokens.push((": ".to_string(), TokenKind::Colon));
+                tokens.extend(field.shape.inline(depth + 1));
+            }
+            tokens.push(("}".to_string(), TokenKind::Bracket(depth)));
+            alternatives.push(tokens);
+        }
+
+        // A single type that can also be null is written `T?`.
+        match alternatives.len() {
+            0 if self.null => vec![("null".to_string(), TokenKind::Null)],
+            0 => vec![("unknown".to_string(), TokenKind::Annotation)],
+            1 if self.null => {
+                let mut tokens = alternatives.remove(0);
+                tokens.push(("?".to_string(), TokenKind::Punctuation));
+                tokens
+            }
+            _ => {
+                if self.null {
+                    alternatives.push(vec![("null".to_string(), TokenKind::Null)]);
+                }
+                let mut tokens = Vec::new();
+                for (i, alternative) in alternatives.into_iter().enumerate() {
+                    if i > 0 {
+                        tokens.push((" | ".to_string(), TokenKind::Punctuation));
+                    }
+                    tokens.extend(alternative);
+                }
+                tokens
+            }
+        }
+    }
+
+    /// The fields to list one per line instead of inline, when this is an
+    /// object, or an array of them, and nothing else; with the `[]` to add
+    /// to the key for each array level.
+    fn expandable(&self) -> Option<(&BTreeMap<String, Field>, usize, String)> {
+        if self.null || self.boolean || self.int || self.float || self.string {
+            return None;
+        }
+        match (&self.object, &self.array) {
+            (Some((fields, count)), None) if !fields.is_empty() => Some((fields, *count, String::new())),
+            (None, Some(items)) => items.expandable().map(|(fields, count, suffix)| (fields, count, format!("[]{}", suffix))),
+            _ => None,
+        }
+    }
+
+    /// This shape without null, for expanding objects that are sometimes null.
+    fn without_null(&self) -> Shape {
+        Shape { null: false, ..self.clone() }
+    }
+}
+
+/// A key as written in the shape, quoted unless it is a plain identifier,
+/// with `?` when some objects lacked it.
+fn field_label(key: &str, field: &Field, count: usize) -> Vec<(String, TokenKind)> {
+    let plain = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    let key = if plain { key.to_string() } else { serde_json::to_string(key).unwrap_or_default() };
+    let mut tokens = vec![(key, TokenKind::Key)];
+    if field.seen < count {
+        tokens.push(("?".to_string(), TokenKind::Punctuation));
+    }
+    tokens
+}
+
+fn paint(tokens: &[(String, TokenKind)], theme: &Theme) -> String {
+    tokens.iter().map(|(text, kind)| colorize(text, *kind, theme)).collect()
+}
+
+fn width(tokens: &[(String, TokenKind)]) -> usize {
+    tokens.iter().map(|(text, _)| text.chars().count()).sum()
+}
+
+/// Render the shape of `value` as a tree with one key per line, keeping a
+/// key's shape on its line when it fits in `--width`. Arrays of objects are
+/// expanded under `key[]`; `key?` marks keys missing from some objects and
+/// `T?` (or `(nullable)` on an expanded key) types that are sometimes null.
+pub fn render(value: &Value, config: &Config, theme: &Theme) -> String {
+    let shape = Shape::of(value);
+    let mut lines = Vec::new();
+    match shape.expandable() {
+        Some((fields, count, suffix)) if !fits(&[], &shape.inline(0), 0, config) => {
+            if !suffix.is_empty() {
+                lines.push(format!("{}{}", colorize(&suffix, TokenKind::Bracket(0), theme), colorize(":", TokenKind::Colon, theme)));
+            }
+            let level = usize::from(!suffix.is_empty());
+            push_fields(&mut lines, fields, count, level, config, theme);
+        }
+        _ => lines.push(paint(&shape.inline(0), theme)),
+    }
+    lines.join("\n")
+}
+
+fn push_fields(lines: &mut Vec<String>, fields: &BTreeMap<String, Field>, count: usize, level: usize, config: &Config, theme: &Theme) {
+    let indent = " ".repeat(level * config.indent);
+    for (key, field) in fields {
+        let label = field_label(key, field, count);
+        let colon = colorize(":", TokenKind::Colon, theme);
+        let inline = field.shape.inline(level + 1);
+        let non_null = field.shape.without_null();
+        match non_null.expandable() {
+            Some((inner, inner_count, suffix)) if !fits(&label, &inline, indent.len(), config) => {
+                let nullable = if field.shape.null { format!(" {}", colorize("(nullable)", TokenKind::Annotation, theme)) } else { String::new() };
+                lines.push(format!("{indent}{}{}{colon}{nullable}", paint(&label, theme), colorize(&suffix, TokenKind::Bracket(level + 1), theme)));
+                push_fields(lines, inner, inner_count, level + 1, config, theme);
+            }
+            _ => lines.push(format!("{indent}{}{colon} {}", paint(&label, theme), paint(&inline, theme))),
+        }
+    }
+}
+
+/// Whether `label: shape` fits on one line after `indent` columns.
+fn fits(label: &[(String, TokenKind)], shape: &[(String, TokenKind)], indent: usize, config: &Config) -> bool {
+    let used = if label.is_empty() { 0 } else { width(label) + 2 };
+    config.width.is_some_and(|limit| indent + used + width(shape) <= limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn plain(value: &Value, width: Option<usize>) -> String {
+        render(value, &Config { width, ..Default::default() }, &Theme::plain())
+    }
+
+    #[test]
+    fn test_inline_shape() {
+        let value = json!({"users": [
+            {"id": 1, "email": "a@example.com", "tags": ["x"]},
+            {"id": 2, "email": null, "tags": []},
+            {"id": 3, "nick": "c", "tags": []}
+        ]});
+        assert_eq!(plain(&value, Some(80)), "{users: [{email?: string?, id: int, nick?: string, tags: [string]}]}");
+        assert_eq!(plain(&json!([1, 2.5, "x", null, []]), Some(80)), "[number | string | [] | null]");
+        assert_eq!(plain(&json!({"a b": null, "e": []}), Some(80)), "{\"a b\": null, e: []}");
+        assert_eq!(plain(&json!([]), Some(80)), "[]");
+    }
+
+    #[test]
+    fn test_expanded_shape() {
+        let value = json!({
+            "users": [
+                {"id": 1, "email": "a@example.com", "tags": ["x"], "address": {"city": "Oslo", "zip": "0150"}},
+                {"id": 2, "email": null, "tags": [], "address": null}
+            ],
+            "total": 2
+        });
+        assert_eq!(plain(&value, None), [
+            "total: int",
+            "users[]:",
+            "  address: (nullable)",
+            "    city: string",
+            "    zip: string",
+            "  email: string?",
+            "  id: int",
+            "  tags: [string]",
+        ].join("\n"));
+        assert_eq!(plain(&value, Some(40)), [
+            "total: int",
+            "users[]:",
+            "  address: {city: string, zip: string}?",
+            "  email: string?",
+            "  id: int",
+            "  tags: [string]",
+        ].join("\n"));
+        assert_eq!(plain(&json!([{"a": 1}, {"a": 2.5}]), None), "[]:\n  a: number");
+
+        let rainbow = Theme::new("rainbow", false).unwrap();
+        let colored = render(&value, &Config { width: Some(40), ..Default::default() }, &rainbow);
+        assert!(colored.contains(&colorize("string", TokenKind::String, &rainbow)));
+        assert!(colored.contains(&colorize("users", TokenKind::Key, &rainbow)));
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use serde::Serialize;
+use serde_json::Value;
+use crate::config::Config;
+use crate::formatter::{format_value, group_digits};
+use crate::path::{format_path, JsonPath, PathSegment};
+use crate::theme::{colorize, Theme, TokenKind};
+
+/// Node types in the order the table lists them.
+const TYPES: &[&str] = &["object", "array", "string", "integer", "float", "boolean", "null"];
+
+/// Paths and samples longer than this are cut in the table.
+const MAX_CELL: usize = 60;
+
+/// An overview of a document's structure, as printed by `jsonfizz stats`.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub nodes: usize,
+    /// Node count per type: object, array, string, integer, float, boolean, null.
+    pub types: BTreeMap<&'static str, usize>,
+    /// Nesting depth of the deepest node; the root is at depth 0.
+    pub max_depth: usize,
+    pub largest_arrays: Vec<Ranked>,
+    /// Longest strings by character count.
+    pub longest_strings: Vec<Ranked>,
+    /// The most frequent object keys, anywhere in the document.
+    pub keys: Vec<KeyCount>,
+    /// Compact JSON size of each top-level entry, largest first.
+    pub top_level_sizes: Vec<Ranked>,
+    /// Every distinct path, with array indexes written as `[]`.
+    pub paths: Vec<PathStats>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Ranked {
+    pub path: String,
+    pub size: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct KeyCount {
+    pub key: String,
+    pub count: usize,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PathStats {
+    pub path: String,
+    pub count: usize,
+    /// The types seen at this path, in [`TYPES`] order.
+    pub types: Vec<&'static str>,
+    /// The first scalar value seen at this path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample: Option<Value>,
+}
+
+/// Walk `value` and collect its [`Stats`], listing `top` entries for the
+/// ranked sections.
+pub fn collect(value: &Value, top: usize) -> Stats {
+    let mut walker = Walker { top, ..Default::default() };
+    walker.visit(value, 0, &mut Vec::new(), &mut String::new());
+
+    let mut keys: Vec<KeyCount> = walker.keys.into_iter().map(|(key, count)| KeyCount { key, count }).collect();
+    keys.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    keys.truncate(top);
+
+    let mut top_level_sizes: Vec<Ranked> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| Ranked { path: k.clone(), size: compact_size(v) }).collect(),
+        _ => Vec::new(),
+    };
+    top_level_sizes.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    top_level_sizes.truncate(top);
+
+    Stats {
+        nodes: walker.nodes,
+        types: walker.types,
+        max_depth: walker.max_depth,
+        largest_arrays: walker.largest_arrays,
+        longest_strings: walker.longest_strings,
+        keys,
+        top_level_sizes,
+        paths: walker.paths.into_iter().map(|(path, (count, types, sample))| PathStats {
+            path,
+            count,
+            types: TYPES.iter().copied().filter(|t| types.contains(t)).collect(),
+            sample,
+        }).collect(),
+    }
+}
+
+#[derive(Default)]
+struct Walker {
+    top: usize,
+    nodes: usize,
+    types: BTreeMap<&'static str, usize>,
+    max_depth: usize,
+    largest_arrays: Vec<Ranked>,
+    longest_strings: Vec<Ranked>,
+    keys: BTreeMap<String, usize>,
+    paths: BTreeMap<String, (usize, Vec<&'static str>, Option<Value>)>,
+}
+
+impl Walker {
+    /// Count `value` at `path`; `pattern` is the path with its indexes
+    /// written as `[]`.
+    fn visit(&mut self, value: &Value, depth: usize, path: &mut JsonPath, pattern: &mut String) {
+        let kind = type_name(value);
+        self.nodes += 1;
+        *self.types.entry(kind).or_default() += 1;
+        self.max_depth = self.max_depth.max(depth);
+        if !path.is_empty() {
+            match self.paths.get_mut(pattern.as_str()) {
+                Some((count, types, sample)) => {
+                    *count += 1;
+                    if !types.contains(&kind) {
+                        types.push(kind);
+                    }
+                    if sample.is_none() && !value.is_array() && !value.is_object() {
+                        *sample = Some(value.clone());
+                    }
+                }
+                None => {
+                    let sample = (!value.is_array() && !value.is_object()).then(|| value.clone());
+                    self.paths.insert(pattern.clone(), (1, vec![kind], sample));
+                }
+            }
+        }
+
+        let len = pattern.len();
+        match value {
+            Value::Array(arr) => {
+                offer(&mut self.largest_arrays, self.top, arr.len(), path);
+                pattern.push_str("[]");
+                for (i, v) in arr.iter().enumerate() {
+                    path.push(PathSegment::Index(i));
+                    self.visit(v, depth + 1, path, pattern);
+                    path.pop();
+                }
+            }
+            Value::Object(map) => {
+                for (k, v) in map {
+                    *self.keys.entry(k.clone()).or_default() += 1;
+                    if !pattern.is_empty() {
+                        pattern.push('.');
+                    }
+                    pattern.push_str(k);
+                    path.push(PathSegment::Key(k.clone()));
+                    self.visit(v, depth + 1, path, pattern);
+                    path.pop();
+                    pattern.truncate(len);
+                }
+            }
+            Value::String(s) => offer(&mut self.longest_strings, self.top, s.chars().count(), path),
+            _ => {}
+        }
+        pattern.truncate(len);
+    }
+}
+
+/// Add `path` to the `top` largest in `ranked`, which is kept sorted largest
+/// first; ties keep the earlier path.
+fn offer(ranked: &mut Vec<Ranked>, top: usize, size: usize, path: &JsonPath) {
+    if ranked.len() == top && ranked.last().is_none_or(|last| last.size >= size) {
+        return;
+    }
+    let at = ranked.iter().position(|r| r.size < size).unwrap_or(ranked.len());
+    ranked.insert(at, Ranked { path: format_path(path), size });
+    ranked.truncate(top);
+}
+
+pub(crate) fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "integer",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+fn compact_size(value: &Value) -> usize {
+    serde_json::to_vec(value).map_or(0, |bytes| bytes.len())
+}
+
+/// Render `stats` as aligned plain-text tables, drawing paths, keys and
+/// sample values in `theme`.
+pub fn render_table(stats: &Stats, theme: &Theme) -> String {
+    let mut out = String::new();
+    let label = |text: &str| colorize(text, TokenKind::Key, theme);
+    let number = |n: usize| colorize(&group_digits(n), TokenKind::Integer, theme);
+
+    out.push_str(&format!("{}  {}\n", label("nodes    "), number(stats.nodes)));
+    for kind in TYPES {
+        if let Some(&count) = stats.types.get(kind) {
+            out.push_str(&format!("  {:<9}{}\n", kind, number(count)));
+        }
+    }
+    out.push_str(&format!("{}  {}\n", label("max depth"), number(stats.max_depth)));
+
+    let sections = [
+        ("largest arrays (items)", &stats.largest_arrays),
+        ("longest strings (chars)", &stats.longest_strings),
+        ("top-level sizes (bytes)", &stats.top_level_sizes),
+    ];
+    for (title, ranked) in sections {
+        if ranked.is_empty() {
+            continue;
+        }
+        let rows: Vec<(String, String)> = ranked.iter().map(|r| (group_digits(r.size), display_path(&r.path))).collect();
+        push_section(&mut out, title, &rows, theme);
+    }
+    if !stats.keys.is_empty() {
+        let rows: Vec<(String, String)> = stats.keys.iter().map(|k| (format!("{}×", group_digits(k.count)), cut(&k.key))).collect();
+        push_section(&mut out, "keys", &rows, theme);
+    }
+
+    if !stats.paths.is_empty() {
+        out.push_str(&format!("\n{}\n", label("paths")));
+        let sample_config = Config {
+            compact: true,
+            max_string_length: Some(MAX_CELL / 2),
+            ..Default::default()
+        };
+        let path_width = stats.paths.iter().map(|p| cut(&p.path).chars().count()).max().unwrap_or(0);
+        let count_width = stats.paths.iter().map(|p| group_digits(p.count).len()).max().unwrap_or(0);
+        let type_width = stats.paths.iter().map(|p| p.types.join("|").len()).max().unwrap_or(0);
+        for p in &stats.paths {
+            let path = cut(&p.path);
+            let padding = " ".repeat(path_width - path.chars().count());
+            let line = format!("  {}{padding}  {:>count_width$}×  {:<type_width$}", colorize(&path, TokenKind::Key, theme), group_digits(p.count), p.types.join("|"));
+            match &p.sample {
+                Some(sample) => out.push_str(&format!("{line}  {}\n", format_value(sample, &sample_config, theme, 0).unwrap_or_default())),
+                None => out.push_str(&format!("{}\n", line.trim_end())),
+            }
+        }
+    }
+    out
+}
+
+/// A titled list of `(size, path)` rows with the sizes right-aligned.
+fn push_section(out: &mut String, title: &str, rows: &[(String, String)], theme: &Theme) {
+    out.push_str(&format!("\n{}\n", colorize(title, TokenKind::Key, theme)));
+    let width = rows.iter().map(|(size, _)| size.chars().count()).max().unwrap_or(0);
+    for (size, path) in rows {
+        out.push_str(&format!("  {:>width$}  {}\n", size, path));
+    }
+}
+
+fn display_path(path: &str) -> String {
+    if path.is_empty() { "(root)".to_string() } else { cut(path) }
+}
+
+/// `text` cut to [`MAX_CELL`] characters.
+fn cut(text: &str) -> String {
+    if text.chars().count() > MAX_CELL {
+        format!("{}…", text.chars().take(MAX_CELL - 1).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collect() {
+        let value = json!({
+            "users": [
+                {"id": 1, "email": "a@example.com", "tags": ["x"]},
+                {"id": 2, "email": null, "tags": []},
+                {"id": 3.5, "tags": ["y", "zz"]}
+            ],
+            "name": "directory"
+        });
+        let stats = collect(&value, 2);
+        assert_eq!(stats.nodes, 17);
+        assert_eq!(stats.types["object"], 4);
+        assert_eq!(stats.types["array"], 4);
+        assert_eq!(stats.types["string"], 5);
+        assert_eq!(stats.types["integer"], 2);
+        assert_eq!(stats.types["float"], 1);
+        assert_eq!(stats.types["null"], 1);
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(stats.largest_arrays, vec![
+            Ranked { path: "users".to_string(), size: 3 },
+            Ranked { path: "users[2].tags".to_string(), size: 2 },
+        ]);
+        assert_eq!(stats.longest_strings[0], Ranked { path: "users[0].email".to_string(), size: 13 });
+        assert_eq!(stats.keys[0], KeyCount { key: "id".to_string(), count: 3 });
+        assert_eq!(stats.keys[1], KeyCount { key: "tags".to_string(), count: 3 });
+        assert_eq!(stats.top_level_sizes[0].path, "users");
+        assert_eq!(stats.top_level_sizes[1], Ranked { path: "name".to_string(), size: 11 });
+
+        let email = stats.paths.iter().find(|p| p.path == "users[].email").unwrap();
+        assert_eq!((email.count, email.types.clone(), email.sample.clone()), (2, vec!["string", "null"], Some(json!("a@example.com"))));
+        let id = stats.paths.iter().find(|p| p.path == "users[].id").unwrap();
+        assert_eq!(id.types, vec!["integer", "float"]);
+        assert_eq!(stats.paths.iter().find(|p| p.path == "users[].tags[]").unwrap().count, 3);
+        assert_eq!(stats.paths.len(), 7);
+    }
+
+    #[test]
+    fn test_render_table() {
+        let stats = collect(&json!({"a": [1, 2, 3], "b": {"c": "hello"}}), 5);
+        let table = render_table(&stats, &Theme::plain());
+        assert!(table.starts_with("nodes      7\n  object   2\n  array    1\n  string   1\n  integer  3\nmax depth  2\n"));
+        assert!(table.contains("\nlargest arrays (items)\n  3  a\n"));
+        assert!(table.contains("\ntop-level sizes (bytes)\n  13  b\n   7  a\n"));
+        assert!(table.contains("\n  b.c  1×  string   \"hello\"\n"));
+        assert!(table.contains("\n  a    1×  array\n"));
+        assert!(table.contains("\n  a[]  3×  integer  1\n"));
+    }
+}
@@ -0,0 +1,544 @@
+use serde_json::{Map, Number, Value};
+use crate::config::Config;
+use crate::error::JsonfizzError;
+use crate::formatter::string_kind;
+use crate::path::{parse_path_with, JsonPath, PathSegment};
+use crate::theme::{colorize, Theme, TokenKind};
+
+/// The type a CSV column is read as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Integers, floats and booleans as such, empty cells as null, and
+    /// anything else as a string.
+    Auto,
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl ColumnType {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "auto" => ColumnType::Auto,
+            "string" => ColumnType::String,
+            "int" => ColumnType::Int,
+            "float" => ColumnType::Float,
+            "bool" => ColumnType::Bool,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ColumnType::Auto => "auto",
+            ColumnType::String => "string",
+            ColumnType::Int => "int",
+            ColumnType::Float => "float",
+            ColumnType::Bool => "bool",
+        }
+    }
+}
+
+/// The `--delimiter` byte: a single ASCII character, or `tab`.
+pub fn delimiter(config: &Config) -> Result<u8, JsonfizzError> {
+    match config.delimiter.as_str() {
+        "tab" | "\\t" => Ok(b'\t'),
+        d => single_byte(d, "delimiter").map_err(|_| JsonfizzError::Config(format!("Invalid delimiter '{}': use a single ASCII character, or tab", d))),
+    }
+}
+
+/// The `--quote` byte.
+pub fn quote(config: &Config) -> Result<u8, JsonfizzError> {
+    single_byte(&config.quote, "quote character")
+}
+
+fn single_byte(s: &str, what: &str) -> Result<u8, JsonfizzError> {
+    match s.as_bytes() {
+        [b] if b.is_ascii() => Ok(*b),
+        _ => Err(JsonfizzError::Config(format!("Invalid {} '{}': use a single ASCII character", what, s))),
+    }
+}
+
+/// Parse `--column-types`, e.g. `id=int,zip=string`, into column names (or
+/// 0-based indexes) and types.
+fn column_types(spec: &str) -> Result<Vec<(String, ColumnType)>, JsonfizzError> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (column, ty) = entry.rsplit_once('=')
+                .ok_or_else(|| JsonfizzError::Config(format!("Invalid column type '{}': expected column=type", entry)))?;
+            let ty = ColumnType::parse(ty.trim())
+                .ok_or_else(|| JsonfizzError::Config(format!("Unknown column type '{}' for {}: use auto, string, int, float or bool", ty.trim(), column.trim())))?;
+            Ok((column.trim().to_string(), ty))
+        })
+        .collect()
+}
+
+/// Read CSV into an array with one item per record: an object keyed by the
+/// header row, whose dotted names (`user.name`, `tags[0]`) are expanded into
+/// nested objects and arrays, or with `--no-header` an array of the fields.
+/// Field values are typed by [`ColumnType`].
+pub fn parse(input: &str, config: &Config) -> Result<Value, JsonfizzError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(config.header)
+        .delimiter(delimiter(config)?)
+        .quote(quote(config)?)
+        .from_reader(input.as_bytes());
+
+    let headers: Vec<String> = if config.header {
+        reader.headers().map_err(csv_parse_error)?.iter().map(str::to_string).collect()
+    } else {
+        Vec::new()
+    };
+    let overrides = column_types(config.column_types.as_deref().unwrap_or(""))?;
+    for (column, _) in &overrides {
+        let known = headers.iter().any(|h| h == column) || (!config.header && column.parse::<usize>().is_ok());
+        if !known {
+            return Err(JsonfizzError::Config(format!("--column-types names unknown column '{}'", column)));
+        }
+    }
+    let type_of = |index: usize| -> ColumnType {
+        let name = headers.get(index).cloned().unwrap_or_else(|| index.to_string());
+        overrides.iter().rev().find(|(column, _)| *column == name).map_or(ColumnType::Auto, |(_, ty)| *ty)
+    };
+    if config.header {
+        crate::flatten::check_separator(&config.key_separator)?;
+    }
+    let paths = header_paths(&headers, &config.key_separator);
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(csv_parse_error)?;
+        let line = record.position().map(|p| p.line() as usize);
+        if config.header && record.len() != headers.len() {
+            return Err(JsonfizzError::parse_error(
+                "CSV",
+                format!("Record {} has {} fields, expected {}", rows.len() + 1, record.len(), headers.len()),
+                line,
+                None,
+            ));
+        }
+        let mut fields = Vec::with_capacity(record.len());
+        for (index, field) in record.iter().enumerate() {
+            let ty = type_of(index);
+            fields.push(typed(field, ty).ok_or_else(|| {
+                let column = headers.get(index).cloned().unwrap_or_else(|| index.to_string());
+                JsonfizzError::parse_error("CSV", format!("'{}' in column {} is not a valid {}", field, column, ty.name()), line, None)
+            })?);
+        }
+        if !config.header {
+            rows.push(Value::Array(fields));
+            continue;
+        }
+        let Some(paths) = &paths else {
+            rows.push(Value::Object(headers.iter().cloned().zip(fields).collect()));
+            continue;
+        };
+        let mut row = Value::Object(Map::new());
+        let mut slots = headers.len() + crate::flatten::MAX_PADDING;
+        for ((path, header), field) in paths.iter().zip(&headers).zip(fields) {
+            crate::flatten::insert(&mut row, path, field, &mut slots)
+                .map_err(|_| JsonfizzError::Data(format!("Cannot place CSV column '{}' in its row", header)))?;
+        }
+        rows.push(row);
+    }
+    Ok(Value::Array(rows))
+}
+
+/// The path each header is expanded to, or `None` to keep every header as
+/// a plain key because two of them overlap (`user` and `user.name`) or an
+/// index would need too large an array. A header that is not a valid path
+/// is used as a plain key.
+fn header_paths(headers: &[String], separator: &str) -> Option<Vec<JsonPath>> {
+    let paths: Vec<JsonPath> = headers.iter()
+        .map(|h| parse_path_with(h, separator).ok().filter(|p| !p.is_empty()).unwrap_or_else(|| vec![PathSegment::Key(h.clone())]))
+        .collect();
+    let mut scratch = Value::Object(Map::new());
+    let mut slots = headers.len() + crate::flatten::MAX_PADDING;
+    for path in &paths {
+        crate::flatten::insert(&mut scratch, path, Value::Bool(true), &mut slots).ok()?;
+    }
+    Some(paths)
+}
+
+/// `field` read as `ty`, or `None` when it is not of that type. Empty
+/// fields are null except in string columns.
+fn typed(field: &str, ty: ColumnType) -> Option<Value> {
+    if field.is_empty() && ty != ColumnType::String {
+        return Some(Value::Null);
+    }
+    match ty {
+        ColumnType::String => Some(Value::String(field.to_string())),
+        ColumnType::Int => field.parse::<i64>().ok().map(Value::from),
+        ColumnType::Float => field.parse::<f64>().ok().and_then(Number::from_f64).map(Value::Number),
+        ColumnType::Bool => parse_bool(field).map(Value::Bool),
+        // JSON number syntax keeps zip codes like 0150 and values like 1e
+        // or +5 as strings, and so does any number that would not read back
+        // as written, such as an ID too long for an f64.
+        ColumnType::Auto => Some(match serde_json::from_str::<Number>(field) {
+            Ok(n) if n.is_i64() || n.is_u64() || n.to_string() == field => Value::Number(n),
+            _ => parse_bool(field).map_or_else(|| Value::String(field.to_string()), Value::Bool),
+        }),
+    }
+}
+
+fn parse_bool(field: &str) -> Option<bool> {
+    if field.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if field.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// A table's column names and rows of cells, `None` where a row lacks the column.
+pub(crate) type Records = (Vec<String>, Vec<Vec<Option<Value>>>);
+
+/// The header and cells of an array of objects, for CSV and table output:
+/// one row per object, with nested objects flattened into `a.b` columns in
+/// the order keys are first seen, or the `--columns` paths in the order
+/// given. Empty objects add no columns, and rows with no columns at all
+/// come back as no rows.
+pub(crate) fn records(value: &Value, config: &Config, format: &str) -> Result<Records, JsonfizzError> {
+    let Value::Array(arr) = value else {
+        return Err(JsonfizzError::Config(format!("{} output requires a JSON array of objects", format)));
+    };
+    let not_objects: Vec<String> = arr.iter().enumerate()
+        .filter(|(_, item)| !item.is_object())
+        .map(|(i, item)| format!("[{}] ({})", i, crate::stats::type_name(item)))
+        .collect();
+    if !not_objects.is_empty() {
+        const SHOWN: usize = 10;
+        let mut listed = not_objects.iter().take(SHOWN).cloned().collect::<Vec<_>>().join(", ");
+        if not_objects.len() > SHOWN {
+            listed.push_str(&format!(" and {} more", not_objects.len() - SHOWN));
+        }
+        return Err(JsonfizzError::Data(format!("{} output needs every row to be an object, but {} of {} are not: {}", format, not_objects.len(), arr.len(), listed)));
+    }
+    if arr.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    Ok(match &config.columns {
+        Some(columns) => {
+            let headers: Vec<String> = columns.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect();
+            if headers.is_empty() {
+                return Err(JsonfizzError::Config(format!("--columns '{}' names no columns", columns)));
+            }
+            let paths = headers.iter().map(|c| crate::path::parse_path(c)).collect::<Result<Vec<_>, _>>()?;
+            let rows = arr.iter().map(|item| paths.iter().map(|path| crate::path::resolve(item, path).ok()).collect()).collect();
+            (headers, rows)
+        }
+        None => {
+            let flat: Vec<Map<String, Value>> = arr.iter().map(|item| crate::flatten::flatten_objects(item, &config.key_separator)).collect::<Result<_, _>>()?;
+            let mut headers: Vec<String> = Vec::new();
+            let mut seen = std::collections::HashSet::new();
+            for row in &flat {
+                for key in row.keys() {
+                    if seen.insert(key.as_str()) {
+                        headers.push(key.clone());
+                    }
+                }
+            }
+            if headers.is_empty() {
+                return Ok((Vec::new(), Vec::new()));
+            }
+            let rows = flat.iter().map(|row| headers.iter().map(|key| row.get(key).cloned()).collect()).collect();
+            (headers, rows)
+        }
+    })
+}
+
+/// The narrowest a column is squeezed to when fitting a table in `--width`.
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// One table cell as the escaped text of each of its characters, with the
+/// token kind it is colored as. Keeping the escapes apart lets [`Cell::fit`]
+/// cut between them, never through one.
+struct Cell {
+    pieces: Vec<String>,
+    kind: TokenKind,
+}
+
+impl Cell {
+    fn new(value: Option<&Value>, markdown: bool) -> Self {
+        let (text, kind) = match value {
+            None => (String::new(), TokenKind::Null),
+            Some(Value::Null) => ("null".to_string(), TokenKind::Null),
+            Some(Value::Bool(b)) => (b.to_string(), TokenKind::Bool),
+            Some(Value::Number(n)) if n.is_f64() => (n.to_string(), TokenKind::Float),
+            Some(Value::Number(n)) => (n.to_string(), TokenKind::Integer),
+            Some(Value::String(s)) => (s.clone(), string_kind(s)),
+            Some(other) => (other.to_string(), TokenKind::Punctuation),
+        };
+        Cell::text(&text, kind, markdown)
+    }
+
+    fn text(text: &str, kind: TokenKind, markdown: bool) -> Self {
+        Cell { pieces: text.chars().map(|c| escape_cell_char(c, markdown)).collect(), kind }
+    }
+
+    fn width(&self) -> usize {
+        self.pieces.iter().map(|piece| piece.chars().count()).sum()
+    }
+
+    /// The text cut to `width` characters, ending in `…` when cut.
+    fn fit(&self, width: usize) -> String {
+        if self.width() <= width {
+            return self.pieces.concat();
+        }
+        let mut cut = String::new();
+        let mut used = 0;
+        for piece in &self.pieces {
+            let len = piece.chars().count();
+            if used + len >= width {
+                break;
+            }
+            cut.push_str(piece);
+            used += len;
+        }
+        cut.push('…');
+        cut
+    }
+}
+
+/// Keep a cell on one line, control characters off the terminal, and (in
+/// Markdown) the text inside its column.
+fn escape_cell_char(c: char, markdown: bool) -> String {
+    match c {
+        '\n' => "\\n".to_string(),
+        '\r' => "\\r".to_string(),
+        '\t' => "\\t".to_string(),
+        c if c.is_control() => format!("\\u{:04x}", c as u32),
+        '|' if markdown => "\\|".to_string(),
+        c => c.to_string(),
+    }
+}
+
+/// `--format table` and `--format markdown`: an array of objects as an
+/// aligned table with a column per key (columns chosen as for CSV), or a
+/// single object as a key/value table. Cells are colored by value type,
+/// numeric columns are right-aligned, and with `--width` the widest columns
+/// are cut down, their cells ending in `…`, until rows fit.
+pub fn to_table(value: &Value, config: &Config, theme: &Theme, markdown: bool) -> Result<String, JsonfizzError> {
+    let (headers, rows, keyed) = match value {
+        Value::Object(_) => {
+            let rows = crate::flatten::flatten_objects(value, &config.key_separator)?.into_iter()
+                .map(|(key, v)| vec![Some(Value::String(key)), Some(v)])
+                .collect();
+            (vec!["key".to_string(), "value".to_string()], rows, true)
+        }
+        _ => {
+            let (headers, rows) = records(value, config, if markdown { "Markdown" } else { "Table" })?;
+            (headers, rows, false)
+        }
+    };
+    if rows.is_empty() {
+        return Ok(String::new());
+    }
+
+    let header: Vec<Cell> = headers.iter().map(|h| Cell::text(h, TokenKind::Key, markdown)).collect();
+    let mut body: Vec<Vec<Cell>> = rows.iter().map(|row| row.iter().map(|v| Cell::new(v.as_ref(), markdown)).collect()).collect();
+    if keyed {
+        for row in &mut body {
+            row[0].kind = TokenKind::Key;
+        }
+    }
+    let numeric: Vec<bool> = (0..headers.len())
+        .map(|i| {
+            let mut values = rows.iter().filter_map(|row| row[i].as_ref()).filter(|v| !v.is_null()).peekable();
+            values.peek().is_some() && values.all(Value::is_number)
+        })
+        .collect();
+
+    let floor = if markdown { MIN_COLUMN_WIDTH } else { 1 };
+    let mut widths: Vec<usize> = (0..headers.len())
+        .map(|i| body.iter().map(|row| row[i].width()).chain([header[i].width(), floor]).max().unwrap_or(floor))
+        .collect();
+    if let Some(limit) = config.width {
+        let gaps = if markdown { 3 * headers.len() + 1 } else { 2 * (headers.len() - 1) };
+        while widths.iter().sum::<usize>() + gaps > limit {
+            let Some(widest) = (0..widths.len()).max_by_key(|&i| (widths[i], std::cmp::Reverse(i))) else { break };
+            if widths[widest] <= MIN_COLUMN_WIDTH {
+                break;
+            }
+            widths[widest] -= 1;
+        }
+    }
+
+    let line = |cells: &[Cell]| -> String {
+        let rendered = cells.iter().enumerate().map(|(i, cell)| {
+            let text = cell.fit(widths[i]);
+            let pad = " ".repeat(widths[i] - text.chars().count());
+            let text = if text.is_empty() { text } else { colorize(&text, cell.kind, theme) };
+            if numeric[i] {
+                format!("{pad}{text}")
+            } else {
+                format!("{text}{pad}")
+            }
+        });
+        if markdown {
+            let bar = colorize("|", TokenKind::Punctuation, theme);
+            format!("{bar} {} {bar}", rendered.collect::<Vec<_>>().join(&format!(" {bar} ")))
+        } else {
+            rendered.collect::<Vec<_>>().join("  ").trim_end().to_string()
+        }
+    };
+
+    let rule = if markdown {
+        let bar = colorize("|", TokenKind::Punctuation, theme);
+        let dashes = widths.iter().zip(&numeric).map(|(&w, &right)| {
+            let dashes = if right { format!("{}:", "-".repeat(w - 1)) } else { "-".repeat(w) };
+            colorize(&dashes, TokenKind::Punctuation, theme)
+        });
+        format!("{bar} {} {bar}", dashes.collect::<Vec<_>>().join(&format!(" {bar} ")))
+    } else {
+        widths.iter().map(|&w| colorize(&"─".repeat(w), TokenKind::Punctuation, theme)).collect::<Vec<_>>().join("  ")
+    };
+
+    let mut lines = vec![line(&header), rule];
+    lines.extend(body.iter().map(|row| line(row)));
+    Ok(lines.join("\n"))
+}
+
+fn csv_parse_error(err: csv::Error) -> JsonfizzError {
+    let (line, column) = if let Some(pos) = err.position() {
+        (Some(pos.line() as usize), None)
+    } else {
+        (None, None)
+    };
+    JsonfizzError::parse_error("CSV", err.to_string(), line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_type_inference_and_nesting() {
+        let input = "id,user.name,user.zip,score,active,note,tags[0]\n1,Ann,0150,9.5,true,,x\n2,Bo,1234,-3,FALSE,\"a, b\",\n";
+        let value = parse(input, &Config::default()).unwrap();
+        assert_eq!(value, json!([
+            {"id": 1, "user": {"name": "Ann", "zip": "0150"}, "score": 9.5, "active": true, "note": null, "tags": ["x"]},
+            {"id": 2, "user": {"name": "Bo", "zip": 1234}, "score": -3, "active": false, "note": "a, b", "tags": [null]}
+        ]));
+
+        let typed_config = Config { column_types: Some("user.zip=string, score=float,note=string".to_string()), ..Default::default() };
+        let value = parse(input, &typed_config).unwrap();
+        assert_eq!(value[1]["user"]["zip"], json!("1234"));
+        assert_eq!(value[1]["score"], json!(-3.0));
+        assert_eq!(value[0]["note"], json!(""));
+
+        let ids = parse("id,ratio,exp\n12345678901234567890123,2.50,1E3\n18446744073709551615,0.1,1e3\n", &Config::default()).unwrap();
+        assert_eq!(ids, json!([
+            {"id": "12345678901234567890123", "ratio": "2.50", "exp": "1E3"},
+            {"id": 18446744073709551615u64, "ratio": 0.1, "exp": "1e3"}
+        ]));
+
+        let bad = Config { column_types: Some("user.name=int".to_string()), ..Default::default() };
+        let err = parse(input, &bad).unwrap_err().to_string();
+        assert!(err.contains("'Ann' in column user.name is not a valid int"), "{}", err);
+        let unknown = Config { column_types: Some("nope=int".to_string()), ..Default::default() };
+        assert!(matches!(parse(input, &unknown), Err(JsonfizzError::Config(_))));
+    }
+
+    #[test]
+    fn test_overlapping_headers_stay_flat() {
+        assert_eq!(parse("user,user.name\nann,Ann\n", &Config::default()).unwrap(), json!([{"user": "ann", "user.name": "Ann"}]));
+        assert_eq!(parse("a[4000000000],b.c\n1,2\n", &Config::default()).unwrap(), json!([{"a[4000000000]": 1, "b.c": 2}]));
+        assert_eq!(parse("a[2],b\n1,2\n", &Config::default()).unwrap(), json!([{"a": [null, null, 1], "b": 2}]));
+    }
+
+    #[test]
+    fn test_headerless_delimiter_and_quote() {
+        let config = Config {
+            header: false,
+            delimiter: "tab".to_string(),
+            quote: "'".to_string(),
+            column_types: Some("1=string".to_string()),
+            ..Default::default()
+        };
+        let value = parse("1\t007\t'a\tb'\n2\t8\t\n", &config).unwrap();
+        assert_eq!(value, json!([[1, "007", "a\tb"], [2, "8", null]]));
+
+        let semicolon = Config { delimiter: ";".to_string(), ..Default::default() };
+        assert_eq!(parse("a;b\n1;x\n", &semicolon).unwrap(), json!([{"a": 1, "b": "x"}]));
+        assert!(matches!(parse("a\n1\n", &Config { quote: "''".to_string(), ..Default::default() }), Err(JsonfizzError::Config(_))));
+    }
+
+    #[test]
+    fn test_text_and_markdown_tables() {
+        let value = json!([
+            {"id": 1, "user": {"name": "Ann"}, "note": "a|b"},
+            {"id": 20, "user": {"name": "Bartholomew"}, "score": 9.5}
+        ]);
+        let plain = Theme::plain();
+        assert_eq!(to_table(&value, &Config::default(), &plain, false).unwrap(), [
+            "id  note  user.name    score",
+            "──  ────  ───────────  ─────",
+            " 1  a|b   Ann",
+            "20        Bartholomew    9.5",
+        ].join("\n"));
+        assert_eq!(to_table(&value, &Config::default(), &plain, true).unwrap(), [
+            "|  id | note | user.name   | score |",
+            "| --: | ---- | ----------- | ----: |",
+            "|   1 | a\\|b | Ann         |       |",
+            "|  20 |      | Bartholomew |   9.5 |",
+        ].join("\n"));
+
+        let narrow = Config { width: Some(24), ..Default::default() };
+        assert_eq!(to_table(&value, &narrow, &plain, false).unwrap(), [
+            "id  note  user.n…  score",
+            "──  ────  ───────  ─────",
+            " 1  a|b   Ann",
+            "20        Bartho…    9.5",
+        ].join("\n"));
+
+        let rainbow = Theme::new("rainbow", false).unwrap();
+        let colored = to_table(&value, &Config::default(), &rainbow, false).unwrap();
+        assert!(colored.contains(&colorize("Ann", TokenKind::String, &rainbow)));
+        assert!(colored.contains(&colorize("9.5", TokenKind::Float, &rainbow)));
+    }
+
+    #[test]
+    fn test_key_value_table_and_errors() {
+        let value = json!({"name": "jsonfizz", "db": {"port": 5432}, "tags": ["a"]});
+        assert_eq!(to_table(&value, &Config::default(), &Theme::plain(), false).unwrap(), [
+            "key      value",
+            "───────  ────────",
+            "db.port  5432",
+            "name     jsonfizz",
+            "tags     [\"a\"]",
+        ].join("\n"));
+        assert_eq!(to_table(&json!([]), &Config::default(), &Theme::plain(), true).unwrap(), "");
+        assert_eq!(to_table(&json!({}), &Config::default(), &Theme::plain(), false).unwrap(), "");
+        let err = to_table(&json!([{"a": 1}, 2]), &Config::default(), &Theme::plain(), true).unwrap_err().to_string();
+        assert!(err.contains("Markdown output needs every row to be an object"), "{}", err);
+        assert!(matches!(to_table(&json!("x"), &Config::default(), &Theme::plain(), false), Err(JsonfizzError::Config(_))));
+        let no_columns = Config { columns: Some(",".to_string()), width: Some(80), ..Default::default() };
+        assert!(matches!(to_table(&json!([{"a": 1}]), &no_columns, &Theme::plain(), false), Err(JsonfizzError::Config(_))));
+    }
+
+    #[test]
+    fn test_empty_object_rows_add_no_columns() {
+        let value = json!([{}, {"a": 1}]);
+        assert_eq!(to_table(&value, &Config::default(), &Theme::plain(), true).unwrap(), "|   a |\n| --: |\n|     |\n|   1 |");
+        let (headers, rows) = records(&value, &Config::default(), "CSV").unwrap();
+        assert_eq!(headers, ["a"]);
+        assert_eq!(rows, [vec![None], vec![Some(json!(1))]]);
+        assert_eq!(records(&json!([{}, {}]), &Config::default(), "CSV").unwrap(), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn test_cells_are_cut_between_escapes() {
+        let value = json!([{"a": "x|y|z", "b": "\u{1b}[31mred"}]);
+        let narrow = Config { width: Some(13), ..Default::default() };
+        let table = to_table(&value, &narrow, &Theme::plain(), true).unwrap();
+        assert_eq!(table.lines().nth(2).unwrap(), "| x…  | …   |");
+        let wide = to_table(&value, &Config::default(), &Theme::plain(), false).unwrap();
+        assert!(wide.ends_with("x|y|z  \\u001b[31mred"), "{}", wide);
+    }
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+use crate::theme::Background;
+
+/// How long to wait for the terminal to answer a query before giving up.
+const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Work out whether the terminal has a light or dark background, first from
+/// `COLORFGBG` and otherwise by asking the terminal with an OSC 11 query.
+///
+/// Returns `None` when stdout is not a terminal or the terminal does not say.
+pub fn detect_background() -> Option<Background> {
+    if let Some(background) = std::env::var("COLORFGBG").ok().as_deref().and_then(parse_colorfgbg) {
+        return Some(background);
+    }
+    if !atty::is(atty::Stream::Stdout) {
+        return None;
+    }
+    query_background(QUERY_TIMEOUT).as_deref().and_then(parse_osc11_response)
+}
+
+/// Parse `COLORFGBG`, which rxvt-style terminals set to `fg;bg` (or
+/// `fg;default;bg`) using ANSI palette indexes.
+pub fn parse_colorfgbg(value: &str) -> Option<Background> {
+    let bg: u8 = value.rsplit(';').next()?.trim().parse().ok()?;
+    Some(match bg {
+        7 | 15 => Background::Light,
+        _ => Background::Dark,
+    })
+}
+
+/// Parse the terminal's reply to `OSC 11 ; ?`, e.g. `ESC ] 11 ; rgb:ffff/ffff/ffff BEL`.
+/// Each channel has one to four hex digits.
+pub fn parse_osc11_response(response: &[u8]) -> Option<Background> {
+    let text = String::from_utf8_lossy(response);
+    let body = &text[text.find("]11;")? + 4..];
+    let spec = &body[..body.find(['\x07', '\x1b']).unwrap_or(body.len())];
+    let channels = spec.strip_prefix("rgb:").or_else(|| spec.strip_prefix("rgba:"))?;
+    let levels = channels
+        .split('/')
+        .take(3)
+        .map(|hex| {
+            if hex.is_empty() || hex.len() > 4 {
+                return None;
+            }
+            let max = (1u32 << (4 * hex.len())) - 1;
+            u32::from_str_radix(hex, 16).ok().map(|v| v as f64 / max as f64)
+        })
+        .collect::<Option<Vec<f64>>>()?;
+    match levels[..] {
+        [r, g, b] => Some(Background::from_luminance(0.2126 * r + 0.7152 * g + 0.0722 * b)),
+        _ => None,
+    }
+}
+
+/// Send an OSC 11 background query to the controlling terminal and collect
+/// the reply. A device attributes query is sent right after it: every
+/// terminal answers that one, so its reply ends the wait early on terminals
+/// that ignore OSC 11.
+#[cfg(unix)]
+fn query_background(timeout: Duration) -> Option<Vec<u8>> {
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    let fd = tty.as_raw_fd();
+    let mut original = std::mem::MaybeUninit::<libc::termios>::uninit();
+    // SAFETY: `fd` is open for the lifetime of `tty`, and `original` is only
+    // read after tcgetattr has filled it in.
+    let original = unsafe {
+        if libc::tcgetattr(fd, original.as_mut_ptr()) != 0 {
+            return None;
+        }
+        original.assume_init()
+    };
+    let mut raw = original;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+    raw.c_cc[libc::VMIN] = 0;
+    raw.c_cc[libc::VTIME] = 0;
+    // SAFETY: `raw` is a valid termios copied from the terminal's own settings.
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+        return None;
+    }
+
+    let mut response = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").and_then(|_| tty.flush()).is_ok() {
+        let deadline = Instant::now() + timeout;
+        let mut buf = [0u8; 64];
+        while !has_device_attributes(&response) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut pollfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+            // SAFETY: `pollfd` is a single valid entry for an open descriptor.
+            let ready = unsafe { libc::poll(&mut pollfd, 1, remaining.as_millis() as libc::c_int) };
+            if ready <= 0 {
+                break;
+            }
+            match tty.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => response.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+    // SAFETY: restores the settings read above on the same descriptor.
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original) };
+    Some(response)
+}
+
+#[cfg(not(unix))]
+fn query_background(_timeout: Duration) -> Option<Vec<u8>> {
+    None
+}
+
+/// Whether `response` contains a complete primary device attributes reply (`ESC [ ? ... c`).
+#[cfg_attr(not(unix), allow(dead_code))]
+fn has_device_attributes(response: &[u8]) -> bool {
+    response
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .is_some_and(|start| response[start..].contains(&b'c'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_colorfgbg() {
+        assert_eq!(parse_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(parse_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(parse_colorfgbg("0;default;7"), Some(Background::Light));
+        assert_eq!(parse_colorfgbg("default"), None);
+    }
+
+    #[test]
+    fn test_parse_osc11_response() {
+        assert_eq!(parse_osc11_response(b"\x1b]11;rgb:ffff/ffff/ffff\x07"), Some(Background::Light));
+        assert_eq!(parse_osc11_response(b"\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\\x1b[?62;22c"), Some(Background::Dark));
+        assert_eq!(parse_osc11_response(b"\x1b]11;rgb:fd/f6/e3\x07"), Some(Background::Light));
+        assert_eq!(parse_osc11_response(b"\x1b[?62;22c"), None);
+        assert_eq!(parse_osc11_response(b"\x1b]11;rgb:zz/00/00\x07"), None);
+    }
+
+    #[test]
+    fn test_has_device_attributes() {
+        assert!(!has_device_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?62"));
+        assert!(has_device_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;22c"));
+    }
+}
@@ -1,8 +1,254 @@
-use owo_colors::{OwoColorize, Style};
-use owo_colors::colors::*;
+use owo_colors::{AnsiColors, DynColors, OwoColorize, Style};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use crate::error::JsonfizzError;
 
-#[derive(Clone, Copy, Debug)]
+pub const BUILTIN_THEMES: &[&str] = &[
+    "default", "solarized", "mono", "rainbow", "ocean", "forest",
+    "pastel", "sakura", "cyberpunk", "ghibli", "evangelion",
+];
+
+const MAX_INHERIT_DEPTH: usize = 16;
+
+/// How many colors the terminal can display. Theme colors beyond this are
+/// downgraded to the nearest color the terminal supports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    #[default]
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Detect the color depth from `COLORTERM` and `TERM`.
+    pub fn detect() -> Self {
+        Self::from_env(std::env::var("COLORTERM").ok().as_deref(), std::env::var("TERM").ok().as_deref())
+    }
+
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        let colorterm = colorterm.unwrap_or("").to_lowercase();
+        let term = term.unwrap_or("").to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" || term.contains("truecolor") || term.contains("direct") {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// Whether the terminal draws text on a light or a dark background.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Background {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Background {
+    /// Classify a background by its luminance, from 0.0 (black) to 1.0 (white).
+    pub fn from_luminance(luminance: f64) -> Self {
+        if luminance > 0.5 {
+            Background::Light
+        } else {
+            Background::Dark
+        }
+    }
+}
+
+/// A color as written in a theme: a named ANSI color, a 256-color palette
+/// index, or a 24-bit RGB value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ThemeColor {
+    Ansi(AnsiColors),
+    Fixed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl ThemeColor {
+    fn depth(self) -> ColorDepth {
+        match self {
+            ThemeColor::Ansi(_) => ColorDepth::Ansi16,
+            ThemeColor::Fixed(_) => ColorDepth::Ansi256,
+            ThemeColor::Rgb(..) => ColorDepth::TrueColor,
+        }
+    }
+
+    fn for_depth(self, depth: ColorDepth) -> DynColors {
+        match (self, depth) {
+            (ThemeColor::Ansi(c), _) => DynColors::Ansi(c),
+            (ThemeColor::Fixed(n), ColorDepth::Ansi16) => DynColors::Ansi(nearest_ansi(xterm_rgb(n))),
+            (ThemeColor::Fixed(n), _) => DynColors::Xterm(n.into()),
+            (ThemeColor::Rgb(r, g, b), ColorDepth::TrueColor) => DynColors::Rgb(r, g, b),
+            (ThemeColor::Rgb(r, g, b), ColorDepth::Ansi256) => DynColors::Xterm(nearest_xterm((r, g, b)).into()),
+            (ThemeColor::Rgb(r, g, b), ColorDepth::Ansi16) => DynColors::Ansi(nearest_ansi((r, g, b))),
+        }
+    }
+}
+
+/// The 16 basic colors with xterm's default RGB values, in palette order.
+const ANSI_PALETTE: [(AnsiColors, (u8, u8, u8)); 16] = [
+    (AnsiColors::Black, (0, 0, 0)),
+    (AnsiColors::Red, (205, 0, 0)),
+    (AnsiColors::Green, (0, 205, 0)),
+    (AnsiColors::Yellow, (205, 205, 0)),
+    (AnsiColors::Blue, (0, 0, 238)),
+    (AnsiColors::Magenta, (205, 0, 205)),
+    (AnsiColors::Cyan, (0, 205, 205)),
+    (AnsiColors::White, (229, 229, 229)),
+    (AnsiColors::BrightBlack, (127, 127, 127)),
+    (AnsiColors::BrightRed, (255, 0, 0)),
+    (AnsiColors::BrightGreen, (0, 255, 0)),
+    (AnsiColors::BrightYellow, (255, 255, 0)),
+    (AnsiColors::BrightBlue, (92, 92, 255)),
+    (AnsiColors::BrightMagenta, (255, 0, 255)),
+    (AnsiColors::BrightCyan, (0, 255, 255)),
+    (AnsiColors::BrightWhite, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Perceptually weighted ("redmean") squared distance between two colors.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i64 {
+    let rmean = (a.0 as i64 + b.0 as i64) / 2;
+    let (dr, dg, db) = (a.0 as i64 - b.0 as i64, a.1 as i64 - b.1 as i64, a.2 as i64 - b.2 as i64);
+    (((512 + rmean) * dr * dr) >> 8) + 4 * dg * dg + (((767 - rmean) * db * db) >> 8)
+}
+
+/// The RGB value of a 256-color palette index.
+pub(crate) fn xterm_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_PALETTE[index as usize].1,
+        16..=231 => {
+            let i = index - 16;
+            (CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[(i / 6 % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+/// A color as named by an SGR escape sequence: a palette index or true color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TermColor {
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl TermColor {
+    pub(crate) fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            TermColor::Indexed(n) => xterm_rgb(n),
+            TermColor::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+}
+
+/// The text attributes set by a run of SGR escape sequences, for renderers
+/// that do not write to a terminal themselves.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TextStyle {
+    pub(crate) fg: Option<TermColor>,
+    pub(crate) bg: Option<TermColor>,
+    pub(crate) bold: bool,
+    pub(crate) dim: bool,
+    pub(crate) italic: bool,
+    pub(crate) underline: bool,
+}
+
+impl TextStyle {
+    /// The attributes a theme style turns on.
+    pub(crate) fn from_style(style: Style) -> Self {
+        struct Prefix(Style);
+        impl std::fmt::Display for Prefix {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt_prefix(f)
+            }
+        }
+        let mut text_style = TextStyle::default();
+        for seq in Prefix(style).to_string().split("\x1b[").skip(1) {
+            if let Some(params) = seq.strip_suffix('m') {
+                text_style.apply_sgr(params);
+            }
+        }
+        text_style
+    }
+
+    pub(crate) fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u8> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = TextStyle::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                c @ 30..=37 => self.fg = Some(TermColor::Indexed(c - 30)),
+                c @ 90..=97 => self.fg = Some(TermColor::Indexed(c - 90 + 8)),
+                39 => self.fg = None,
+                c @ 40..=47 => self.bg = Some(TermColor::Indexed(c - 40)),
+                c @ 100..=107 => self.bg = Some(TermColor::Indexed(c - 100 + 8)),
+                49 => self.bg = None,
+                c @ (38 | 48) => {
+                    let color = match codes.get(i + 1) {
+                        Some(5) => codes.get(i + 2).map(|n| {
+                            i += 2;
+                            TermColor::Indexed(*n)
+                        }),
+                        Some(2) if codes.len() > i + 4 => {
+                            i += 4;
+                            Some(TermColor::Rgb(codes[i - 2], codes[i - 1], codes[i]))
+                        }
+                        _ => None,
+                    };
+                    if c == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+fn nearest_ansi(rgb: (u8, u8, u8)) -> AnsiColors {
+    ANSI_PALETTE.iter()
+        .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+        .map(|(color, _)| *color)
+        .unwrap_or(AnsiColors::Default)
+}
+
+/// Nearest color in the 6x6x6 cube or the grayscale ramp of the 256-color palette.
+fn nearest_xterm(rgb: (u8, u8, u8)) -> u8 {
+    let level = |c: u8| CUBE_LEVELS.iter().enumerate().min_by_key(|(_, l)| (**l as i16 - c as i16).abs()).map(|(i, _)| i as u8).unwrap_or(0);
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let average = (rgb.0 as u16 + rgb.1 as u16 + rgb.2 as u16) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+    if distance(rgb, xterm_rgb(gray)) < distance(rgb, xterm_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// What a piece of output is. The finer kinds fall back to the style of a
+/// broader one (e.g. `Url` to `String`) when a theme does not set them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TokenKind {
     Key,
     String,
@@ -10,9 +256,26 @@ pub enum TokenKind {
     Bool,
     Null,
     Punctuation,
+    Error,
+    /// `{ } [ ]`, with the nesting depth for rainbow brackets.
+    Bracket(usize),
+    Comma,
+    Colon,
+    /// An escape sequence such as `\n` inside a string or key.
+    Escape,
+    Integer,
+    Float,
+    Url,
+    Date,
+    Uuid,
+    Email,
+    /// A trailing `--annotate` comment.
+    Annotation,
+    /// The `--line-numbers` gutter.
+    Gutter,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Theme {
     pub key: Style,
     pub string: Style,
@@ -20,124 +283,545 @@ pub struct Theme {
     pub boolean: Style,
     pub null: Style,
     pub punctuation: Style,
+    pub error: Style,
+    pub bracket: Style,
+    pub comma: Style,
+    pub colon: Style,
+    pub escape: Style,
+    pub integer: Style,
+    pub float: Style,
+    pub url: Style,
+    pub date: Style,
+    pub uuid: Style,
+    pub email: Style,
+    pub annotation: Style,
+    pub gutter: Style,
+    /// Bracket styles cycled by nesting depth; empty to use `bracket`.
+    pub rainbow_brackets: Vec<Style>,
+}
+
+/// How one token kind is drawn, as written in a theme file.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StyleDef {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+    pub dim: Option<bool>,
+}
+
+/// A theme as written in `themes/<name>.toml` or `[themes.<name>]` in config.
+///
+/// Token kinds that are not set come from the `inherits` theme, attribute by
+/// attribute, or are left unstyled. The finer kinds (`bracket`, `url`, ...)
+/// are drawn over their broader kind, so `[url]` with only `underline = true`
+/// keeps the string color. The optional `[light]` and `[dark]` sections
+/// override token styles on that kind of terminal background.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeDef {
+    pub inherits: Option<String>,
+    pub light: Option<Box<ThemeDef>>,
+    pub dark: Option<Box<ThemeDef>>,
+    pub key: Option<StyleDef>,
+    pub string: Option<StyleDef>,
+    pub number: Option<StyleDef>,
+    #[serde(alias = "bool")]
+    pub boolean: Option<StyleDef>,
+    pub null: Option<StyleDef>,
+    pub punctuation: Option<StyleDef>,
+    pub error: Option<StyleDef>,
+    /// Falls back to `punctuation`.
+    pub bracket: Option<StyleDef>,
+    pub comma: Option<StyleDef>,
+    pub colon: Option<StyleDef>,
+    /// Falls back to `string`.
+    pub escape: Option<StyleDef>,
+    /// Falls back to `number`.
+    pub integer: Option<StyleDef>,
+    pub float: Option<StyleDef>,
+    /// Falls back to `string`.
+    pub url: Option<StyleDef>,
+    pub date: Option<StyleDef>,
+    pub uuid: Option<StyleDef>,
+    pub email: Option<StyleDef>,
+    /// `--annotate` comments; dimmed when not set.
+    pub annotation: Option<StyleDef>,
+    /// The `--line-numbers` gutter; falls back to `annotation`.
+    pub gutter: Option<StyleDef>,
+    /// Bracket styles by nesting depth, repeating when the list runs out.
+    pub rainbow_brackets: Option<Vec<StyleDef>>,
+}
+
+impl StyleDef {
+    fn fg(color: &str) -> Self {
+        Self {
+            fg: Some(color.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn over(self, parent: StyleDef) -> StyleDef {
+        StyleDef {
+            fg: self.fg.or(parent.fg),
+            bg: self.bg.or(parent.bg),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underline: self.underline.or(parent.underline),
+            dim: self.dim.or(parent.dim),
+        }
+    }
+
+    fn to_style(&self, token: &str, depth: ColorDepth) -> Result<Style, String> {
+        let mut style = Style::new();
+        if let Some(fg) = &self.fg {
+            style = style.color(pick_color(fg, &format!("{}.fg", token), depth)?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.on_color(pick_color(bg, &format!("{}.bg", token), depth)?);
+        }
+        if self.bold == Some(true) {
+            style = style.bold();
+        }
+        if self.italic == Some(true) {
+            style = style.italic();
+        }
+        if self.underline == Some(true) {
+            style = style.underline();
+        }
+        if self.dim == Some(true) {
+            style = style.dimmed();
+        }
+        Ok(style)
+    }
+}
+
+fn merge_style(child: Option<StyleDef>, parent: Option<StyleDef>) -> Option<StyleDef> {
+    match (child, parent) {
+        (Some(c), Some(p)) => Some(c.over(p)),
+        (c, p) => c.or(p),
+    }
+}
+
+fn merge_variant(higher: Option<Box<ThemeDef>>, lower: Option<Box<ThemeDef>>) -> Option<Box<ThemeDef>> {
+    match (higher, lower) {
+        (Some(h), Some(l)) => Some(Box::new(h.layered_over(*l))),
+        (h, l) => h.or(l),
+    }
+}
+
+impl ThemeDef {
+    fn palette(key: &str, string: &str, number: &str, boolean: &str, null: &str, punctuation: &str) -> Self {
+        Self {
+            key: Some(StyleDef::fg(key)),
+            string: Some(StyleDef::fg(string)),
+            number: Some(StyleDef::fg(number)),
+            boolean: Some(StyleDef::fg(boolean)),
+            null: Some(StyleDef::fg(null)),
+            punctuation: Some(StyleDef::fg(punctuation)),
+            error: Some(StyleDef {
+                bold: Some(true),
+                ..StyleDef::fg("bright-red")
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn with_light(mut self, light: ThemeDef) -> Self {
+        self.light = Some(Box::new(light));
+        self
+    }
+
+    /// The styles to draw with on `background`: the matching variant section
+    /// over the base styles.
+    fn for_background(mut self, background: Background) -> ThemeDef {
+        let variant = match background {
+            Background::Light => self.light.take(),
+            Background::Dark => self.dark.take(),
+        };
+        self.light = None;
+        self.dark = None;
+        match variant {
+            Some(variant) => variant.over(self),
+            None => self,
+        }
+    }
+
+    /// Merge a `[themes.<name>]` table from a higher config layer over the
+    /// same table from a lower one, field by field: unlike [`ThemeDef::over`],
+    /// which draws a theme over the one it inherits from, this keeps both
+    /// layers' `inherits` and variant sections.
+    pub(crate) fn layered_over(mut self, mut lower: ThemeDef) -> ThemeDef {
+        let inherits = self.inherits.take().or(lower.inherits.take());
+        let light = merge_variant(self.light.take(), lower.light.take());
+        let dark = merge_variant(self.dark.take(), lower.dark.take());
+        ThemeDef { inherits, light, dark, ..self.over(lower) }
+    }
+
+    fn over(self, parent: ThemeDef) -> ThemeDef {
+        ThemeDef {
+            inherits: parent.inherits,
+            light: None,
+            dark: None,
+            key: merge_style(self.key, parent.key),
+            string: merge_style(self.string, parent.string),
+            number: merge_style(self.number, parent.number),
+            boolean: merge_style(self.boolean, parent.boolean),
+            null: merge_style(self.null, parent.null),
+            punctuation: merge_style(self.punctuation, parent.punctuation),
+            error: merge_style(self.error, parent.error),
+            bracket: merge_style(self.bracket, parent.bracket),
+            comma: merge_style(self.comma, parent.comma),
+            colon: merge_style(self.colon, parent.colon),
+            escape: merge_style(self.escape, parent.escape),
+            integer: merge_style(self.integer, parent.integer),
+            float: merge_style(self.float, parent.float),
+            url: merge_style(self.url, parent.url),
+            date: merge_style(self.date, parent.date),
+            uuid: merge_style(self.uuid, parent.uuid),
+            email: merge_style(self.email, parent.email),
+            annotation: merge_style(self.annotation, parent.annotation),
+            gutter: merge_style(self.gutter, parent.gutter),
+            rainbow_brackets: self.rainbow_brackets.or(parent.rainbow_brackets),
+        }
+    }
+
+    fn build(&self, depth: ColorDepth) -> Result<Theme, String> {
+        let style = |def: &Option<StyleDef>, token: &str| def.as_ref().map_or(Ok(Style::new()), |d| d.to_style(token, depth));
+        let over = |def: &Option<StyleDef>, base: &Option<StyleDef>, token: &str| style(&merge_style(def.clone(), base.clone()), token);
+        let rainbow_brackets = self.rainbow_brackets.iter().flatten().enumerate()
+            .map(|(i, def)| def.to_style(&format!("rainbow_brackets[{}]", i), depth))
+            .collect::<Result<Vec<_>, _>>()?;
+        let dimmed = Some(StyleDef { dim: Some(true), ..Default::default() });
+        Ok(Theme {
+            key: style(&self.key, "key")?,
+            string: style(&self.string, "string")?,
+            number: style(&self.number, "number")?,
+            boolean: style(&self.boolean, "boolean")?,
+            null: style(&self.null, "null")?,
+            punctuation: style(&self.punctuation, "punctuation")?,
+            error: style(&self.error, "error")?,
+            bracket: over(&self.bracket, &self.punctuation, "bracket")?,
+            comma: over(&self.comma, &self.punctuation, "comma")?,
+            colon: over(&self.colon, &self.punctuation, "colon")?,
+            escape: over(&self.escape, &self.string, "escape")?,
+            integer: over(&self.integer, &self.number, "integer")?,
+            float: over(&self.float, &self.number, "float")?,
+            url: over(&self.url, &self.string, "url")?,
+            date: over(&self.date, &self.string, "date")?,
+            uuid: over(&self.uuid, &self.string, "uuid")?,
+            email: over(&self.email, &self.string, "email")?,
+            annotation: over(&self.annotation, &dimmed, "annotation")?,
+            gutter: over(&self.gutter, &merge_style(self.annotation.clone(), dimmed.clone()), "gutter")?,
+            rainbow_brackets,
+        })
+    }
+}
+
+fn builtin(name: &str) -> Option<ThemeDef> {
+    let punctuation = |color: &str| ThemeDef {
+        punctuation: Some(StyleDef::fg(color)),
+        ..Default::default()
+    };
+    Some(match name {
+        "default" => ThemeDef::palette("yellow", "green", "cyan", "magenta", "bright-black", "white")
+            .with_light(ThemeDef {
+                key: Some(StyleDef::fg("blue")),
+                ..punctuation("black")
+            }),
+        "solarized" => ThemeDef::palette("#b58900, yellow", "#859900, green", "#268bd2, blue", "#d33682, magenta", "#586e75, bright-black", "#93a1a1, white")
+            .with_light(punctuation("#657b83, black")),
+        "mono" => ThemeDef::default(),
+        "rainbow" => ThemeDef::palette("red", "green", "yellow", "blue", "magenta", "cyan"),
+        "ocean" => ThemeDef::palette("blue", "cyan", "bright-blue", "bright-cyan", "bright-black", "white")
+            .with_light(punctuation("black")),
+        "forest" => ThemeDef::palette("green", "bright-green", "yellow", "red", "bright-black", "white")
+            .with_light(punctuation("black")),
+        "pastel" => ThemeDef::palette("#f5a6e6, bright-magenta", "#a8e6a1, bright-green", "#9fd8f5, bright-cyan", "#f5e6a1, bright-yellow", "#9a9a9a, bright-black", "#eeeeee, bright-white")
+            .with_light(ThemeDef::palette("#b5179e, magenta", "#2d6a4f, green", "#0077b6, blue", "#b08900, yellow", "#6c6c6c, bright-black", "#444444, black")),
+        "sakura" => ThemeDef::palette("#ff6fb5, bright-magenta", "#ffb3c6, bright-red", "#7fd6e8, bright-cyan", "#ffd36e, bright-yellow", "#a08c96, bright-black", "#fbe4ec, bright-white")
+            .with_light(ThemeDef::palette("#c2185b, magenta", "#d6336c, red", "#00838f, cyan", "#b07d00, yellow", "#8c6f7a, bright-black", "#5c4a52, black")),
+        "cyberpunk" => ThemeDef::palette("#ff2a9d, bright-magenta", "#39ff88, bright-green", "#00e5ff, bright-cyan", "#fff200, bright-yellow", "#c2185b, red", "#f0f0ff, bright-white")
+            .with_light(ThemeDef::palette("#d0006f, magenta", "#00875a, green", "#0077aa, cyan", "#9a7d00, yellow", "#c2185b, red", "#303040, black")),
+        "ghibli" => ThemeDef::palette("#5b9a4b, green", "#e9c46a, yellow", "#5a8fc9, blue", "#d4593c, red", "#8a8a7a, bright-black", "#efe9d8, white")
+            .with_light(ThemeDef::palette("#3f7a32, green", "#a6781c, yellow", "#3a6ea5, blue", "#b5442a, red", "#7a7a6a, bright-black", "#4a4636, black")),
+        "evangelion" => ThemeDef::palette("#a45ee5, bright-magenta", "#3de0d0, bright-cyan", "#6f8cff, bright-blue", "#ff3b3b, bright-red", "#6c757d, bright-black", "#e0e0e0, bright-white")
+            .with_light(ThemeDef::palette("#7b2cbf, magenta", "#00897b, cyan", "#3a56d4, blue", "#d00000, red", "#6c757d, bright-black", "#333333, black")),
+        _ => return None,
+    })
+}
+
+const COLOR_NAMES: &[(&str, AnsiColors)] = &[
+    ("black", AnsiColors::Black),
+    ("red", AnsiColors::Red),
+    ("green", AnsiColors::Green),
+    ("yellow", AnsiColors::Yellow),
+    ("blue", AnsiColors::Blue),
+    ("magenta", AnsiColors::Magenta),
+    ("cyan", AnsiColors::Cyan),
+    ("white", AnsiColors::White),
+    ("bright-black", AnsiColors::BrightBlack),
+    ("bright-red", AnsiColors::BrightRed),
+    ("bright-green", AnsiColors::BrightGreen),
+    ("bright-yellow", AnsiColors::BrightYellow),
+    ("bright-blue", AnsiColors::BrightBlue),
+    ("bright-magenta", AnsiColors::BrightMagenta),
+    ("bright-cyan", AnsiColors::BrightCyan),
+    ("bright-white", AnsiColors::BrightWhite),
+    ("default", AnsiColors::Default),
+];
+
+/// Choose from a comma-separated list of colors, e.g. `"#859900, green"`:
+/// the first one the terminal can show as-is, otherwise the first one
+/// downgraded to the nearest supported color.
+fn pick_color(value: &str, field: &str, depth: ColorDepth) -> Result<DynColors, String> {
+    let colors = value.split(',').map(|c| parse_color(c, field)).collect::<Result<Vec<_>, _>>()?;
+    let chosen = colors.iter().find(|c| c.depth() <= depth).or(colors.first()).copied();
+    chosen
+        .map(|c| c.for_depth(depth))
+        .ok_or_else(|| format!("empty color for {}", field))
+}
+
+fn parse_color(name: &str, field: &str) -> Result<ThemeColor, String> {
+    let trimmed = name.trim();
+    if let Some(hex) = trimmed.strip_prefix('#') {
+        let digits: Option<Vec<u8>> = match hex.len() {
+            _ if !hex.chars().all(|c| c.is_ascii_hexdigit()) => None,
+            6 => (0..3).map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()).collect(),
+            3 => hex.chars().map(|c| c.to_digit(16).map(|d| d as u8 * 17)).collect(),
+            _ => None,
+        };
+        return match digits.as_deref() {
+            Some(&[r, g, b]) => Ok(ThemeColor::Rgb(r, g, b)),
+            _ => Err(format!("invalid hex color '{}' for {} (expected #rrggbb or #rgb)", name, field)),
+        };
+    }
+    if trimmed.chars().all(|c| c.is_ascii_digit()) && !trimmed.is_empty() {
+        return trimmed.parse::<u8>()
+            .map(ThemeColor::Fixed)
+            .map_err(|_| format!("invalid 256-color index '{}' for {} (expected 0-255)", name, field));
+    }
+    let normalized = trimmed.to_lowercase().replace('_', "-");
+    let normalized = match normalized.strip_prefix("bright") {
+        Some(rest) if !rest.starts_with('-') => format!("bright-{}", rest),
+        _ => normalized,
+    };
+    COLOR_NAMES.iter()
+        .find(|(n, _)| *n == normalized)
+        .map(|(_, c)| ThemeColor::Ansi(*c))
+        .ok_or_else(|| format!(
+            "invalid color '{}' for {} (expected #rrggbb, a 256-color index 0-255, or one of {})",
+            name,
+            field,
+            COLOR_NAMES.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", "),
+        ))
+}
+
+/// Built-in themes plus user themes from theme files and config.
+#[derive(Clone, Debug, Default)]
+pub struct ThemeRegistry {
+    inline: BTreeMap<String, ThemeDef>,
+    files: BTreeMap<String, PathBuf>,
+    depth: ColorDepth,
+    background: Background,
+}
+
+impl ThemeRegistry {
+    /// Collect `*.toml` files from the user theme directories, with the
+    /// themes defined inline in config taking precedence over them.
+    pub fn load(inline: &BTreeMap<String, ThemeDef>) -> Self {
+        let mut files = BTreeMap::new();
+        for dir in theme_dirs() {
+            if let Ok(entries) = std::fs::read_dir(&dir) {
+                for path in entries.flatten().map(|e| e.path()) {
+                    if path.extension().is_some_and(|ext| ext == "toml") {
+                        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                            files.entry(stem.to_lowercase()).or_insert(path.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Self::with_files(inline, files).with_color_depth(ColorDepth::detect())
+    }
+
+    pub fn with_files(inline: &BTreeMap<String, ThemeDef>, files: BTreeMap<String, PathBuf>) -> Self {
+        Self {
+            inline: inline.iter().map(|(k, v)| (k.to_lowercase(), v.clone())).collect(),
+            files,
+            depth: ColorDepth::default(),
+            background: Background::default(),
+        }
+    }
+
+    pub fn with_color_depth(mut self, depth: ColorDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Every theme name, built-ins first.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILTIN_THEMES.iter().map(|n| n.to_string()).collect();
+        for name in self.inline.keys().chain(self.files.keys()) {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    pub fn is_user_theme(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.inline.contains_key(&name) || self.files.contains_key(&name)
+    }
+
+    pub fn theme(&self, name: &str, raw: bool) -> Result<Theme, JsonfizzError> {
+        // Resolve even in raw mode so a misspelled or broken theme is reported.
+        let theme = self.resolve(&name.to_lowercase(), 0)?
+            .build(self.depth)
+            .map_err(|e| JsonfizzError::Config(format!("Theme '{}': {}", name, e)))?;
+        Ok(if raw { Theme::plain() } else { theme })
+    }
+
+    fn user_def(&self, name: &str) -> Result<Option<ThemeDef>, JsonfizzError> {
+        if let Some(def) = self.inline.get(name) {
+            return Ok(Some(def.clone()));
+        }
+        match self.files.get(name) {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| JsonfizzError::Config(format!("Failed to read theme {}: {}", path.display(), e)))?;
+                toml::from_str(&content)
+                    .map(Some)
+                    .map_err(|e| JsonfizzError::Config(format!("Invalid theme {}: {}", path.display(), e)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Resolve `name` and its `inherits` chain into one definition for the
+    /// registry's background. Each theme's variant is applied before its
+    /// parent's, so a child's base style still beats a parent's `[light]` one.
+    fn resolve(&self, name: &str, depth: usize) -> Result<ThemeDef, JsonfizzError> {
+        if depth > MAX_INHERIT_DEPTH {
+            return Err(JsonfizzError::Config(format!("Theme '{}': inheritance is nested too deeply (is there a cycle?)", name)));
+        }
+        let def = match self.user_def(name)? {
+            Some(def) => def,
+            None => {
+                return builtin(name)
+                    .map(|def| def.for_background(self.background))
+                    .ok_or_else(|| JsonfizzError::Config(format!("Unknown theme '{}'. Use: {}", name, self.names().join(", "))));
+            }
+        };
+        for variant in [&def.light, &def.dark].into_iter().flatten() {
+            if variant.inherits.is_some() || variant.light.is_some() || variant.dark.is_some() {
+                return Err(JsonfizzError::Config(format!("Theme '{}': [light] and [dark] may only set token styles", name)));
+            }
+        }
+        let parent = def.inherits.clone();
+        let def = def.for_background(self.background);
+        match parent {
+            None => Ok(def),
+            Some(parent) => {
+                let parent = parent.to_lowercase();
+                // A user theme may shadow a built-in and still inherit from it.
+                let parent_def = if parent == name {
+                    builtin(&parent)
+                        .map(|def| def.for_background(self.background))
+                        .ok_or_else(|| JsonfizzError::Config(format!("Theme '{}' inherits from itself", name)))?
+                } else {
+                    self.resolve(&parent, depth + 1)?
+                };
+                Ok(def.over(parent_def))
+            }
+        }
+    }
+}
+
+fn theme_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(config_dir) = dirs::config_dir() {
+        dirs.push(config_dir.join("jsonfizz").join("themes"));
+    }
+    if let Some(home_dir) = dirs::home_dir() {
+        let dir = home_dir.join(".config").join("jsonfizz").join("themes");
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
 }
 
 impl Theme {
+    /// A theme that applies no styling at all.
+    pub fn plain() -> Self {
+        Self {
+            key: Style::new(),
+            string: Style::new(),
+            number: Style::new(),
+            boolean: Style::new(),
+            null: Style::new(),
+            punctuation: Style::new(),
+            error: Style::new(),
+            bracket: Style::new(),
+            comma: Style::new(),
+            colon: Style::new(),
+            escape: Style::new(),
+            integer: Style::new(),
+            float: Style::new(),
+            url: Style::new(),
+            date: Style::new(),
+            uuid: Style::new(),
+            email: Style::new(),
+            annotation: Style::new(),
+            gutter: Style::new(),
+            rainbow_brackets: Vec::new(),
+        }
+    }
+
+    /// Look up a built-in theme for the detected terminal color depth.
+    /// Use [`ThemeRegistry`] to include user themes.
     pub fn new(name: &str, raw: bool) -> Result<Self, JsonfizzError> {
-        if raw {
-            return Ok(Self {
-                key: Style::new(),
-                string: Style::new(),
-                number: Style::new(),
-                boolean: Style::new(),
-                null: Style::new(),
-                punctuation: Style::new(),
-            });
-        }
-        Ok(match name.to_lowercase().as_str() {
-            "default" => Self {
-                key: Style::new().fg::<Yellow>(),
-                string: Style::new().fg::<Green>(),
-                number: Style::new().fg::<Cyan>(),
-                boolean: Style::new().fg::<Magenta>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<White>(),
-            },
-            "solarized" => Self {
-                key: Style::new().fg::<Yellow>(),
-                string: Style::new().fg::<Green>(),
-                number: Style::new().fg::<Blue>(),
-                boolean: Style::new().fg::<Magenta>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<White>(),
-            },
-            "mono" => Self {
-                key: Style::new(),
-                string: Style::new(),
-                number: Style::new(),
-                boolean: Style::new(),
-                null: Style::new(),
-                punctuation: Style::new(),
-            },
-            "rainbow" => Self {
-                key: Style::new().fg::<Red>(),
-                string: Style::new().fg::<Green>(),
-                number: Style::new().fg::<Yellow>(),
-                boolean: Style::new().fg::<Blue>(),
-                null: Style::new().fg::<Magenta>(),
-                punctuation: Style::new().fg::<Cyan>(),
-            },
-            "ocean" => Self {
-                key: Style::new().fg::<Blue>(),
-                string: Style::new().fg::<Cyan>(),
-                number: Style::new().fg::<BrightBlue>(),
-                boolean: Style::new().fg::<BrightCyan>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<White>(),
-            },
-            "forest" => Self {
-                key: Style::new().fg::<Green>(),
-                string: Style::new().fg::<BrightGreen>(),
-                number: Style::new().fg::<Yellow>(),
-                boolean: Style::new().fg::<Red>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<White>(),
-            },
-            "pastel" => Self {
-                key: Style::new().fg::<BrightMagenta>(),
-                string: Style::new().fg::<BrightGreen>(),
-                number: Style::new().fg::<BrightCyan>(),
-                boolean: Style::new().fg::<BrightYellow>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<BrightWhite>(),
-            },
-            "sakura" => Self {
-                key: Style::new().fg::<BrightMagenta>(),
-                string: Style::new().fg::<BrightRed>(),
-                number: Style::new().fg::<BrightCyan>(),
-                boolean: Style::new().fg::<BrightYellow>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<BrightWhite>(),
-            },
-            "cyberpunk" => Self {
-                key: Style::new().fg::<BrightMagenta>(),
-                string: Style::new().fg::<BrightGreen>(),
-                number: Style::new().fg::<BrightCyan>(),
-                boolean: Style::new().fg::<BrightYellow>(),
-                null: Style::new().fg::<Red>(),
-                punctuation: Style::new().fg::<BrightWhite>(),
-            },
-            "ghibli" => Self {
-                key: Style::new().fg::<Green>(),
-                string: Style::new().fg::<Yellow>(),
-                number: Style::new().fg::<Blue>(),
-                boolean: Style::new().fg::<Red>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<White>(),
-            },
-            "evangelion" => Self {
-                key: Style::new().fg::<BrightMagenta>(),
-                string: Style::new().fg::<BrightCyan>(),
-                number: Style::new().fg::<BrightBlue>(),
-                boolean: Style::new().fg::<BrightRed>(),
-                null: Style::new().fg::<BrightBlack>(),
-                punctuation: Style::new().fg::<BrightWhite>(),
-            },
-            _ => return Err(JsonfizzError::Config(format!("Unknown theme '{}'. Use: default, solarized, mono, rainbow, ocean, forest, pastel, sakura, cyberpunk, ghibli, evangelion", name))),
-        })
+        ThemeRegistry::default().with_color_depth(ColorDepth::detect()).theme(name, raw)
+    }
+
+    /// The style this theme gives to `kind`.
+    pub fn style(&self, kind: TokenKind) -> Style {
+        match kind {
+            TokenKind::Key => self.key,
+            TokenKind::String => self.string,
+            TokenKind::Number => self.number,
+            TokenKind::Bool => self.boolean,
+            TokenKind::Null => self.null,
+            TokenKind::Punctuation => self.punctuation,
+            TokenKind::Error => self.error,
+            TokenKind::Bracket(depth) if !self.rainbow_brackets.is_empty() => self.rainbow_brackets[depth % self.rainbow_brackets.len()],
+            TokenKind::Bracket(_) => self.bracket,
+            TokenKind::Comma => self.comma,
+            TokenKind::Colon => self.colon,
+            TokenKind::Escape => self.escape,
+            TokenKind::Integer => self.integer,
+            TokenKind::Float => self.float,
+            TokenKind::Url => self.url,
+            TokenKind::Date => self.date,
+            TokenKind::Uuid => self.uuid,
+            TokenKind::Email => self.email,
+            TokenKind::Annotation => self.annotation,
+            TokenKind::Gutter => self.gutter,
+        }
     }
 }
 
 pub fn colorize(s: &str, kind: TokenKind, theme: &Theme) -> String {
-    let style = match kind {
-        TokenKind::Key => theme.key,
-        TokenKind::String => theme.string,
-        TokenKind::Number => theme.number,
-        TokenKind::Bool => theme.boolean,
-        TokenKind::Null => theme.null,
-        TokenKind::Punctuation => theme.punctuation,
-    };
-    s.style(style).to_string()
+    s.style(theme.style(kind)).to_string()
 }
 
 #[cfg(test)]
@@ -163,6 +847,9 @@ mod tests {
         let theme = Theme::new("nonexistent", false);
         assert!(theme.is_err());
         assert!(theme.unwrap_err().to_string().contains("Unknown theme"));
+        // Without colors the theme is never drawn, but a typo is still reported.
+        assert!(Theme::new("nonexistent", true).is_err());
+        assert_eq!(Theme::new("ocean", true).unwrap().key, Style::new());
     }
 
     #[test]
@@ -175,6 +862,7 @@ mod tests {
         assert_eq!(theme.boolean, owo_colors::Style::new());
         assert_eq!(theme.null, owo_colors::Style::new());
         assert_eq!(theme.punctuation, owo_colors::Style::new());
+        assert_eq!(theme.error, owo_colors::Style::new());
     }
 
     #[test]
@@ -184,4 +872,166 @@ mod tests {
         // Should contain ANSI color codes
         assert!(result.contains("\x1b["));
     }
-}
\ No newline at end of file
+
+    fn inline_registry(defs: &[(&str, &str)]) -> ThemeRegistry {
+        let inline = defs.iter()
+            .map(|(name, src)| (name.to_string(), toml::from_str::<ThemeDef>(src).unwrap()))
+            .collect();
+        ThemeRegistry::with_files(&inline, BTreeMap::new())
+    }
+
+    #[test]
+    fn test_user_theme_with_attributes() {
+        let registry = inline_registry(&[("paper", r#"
+            [key]
+            fg = "blue"
+            bg = "white"
+            bold = true
+            underline = true
+            [string]
+            fg = "bright_green"
+            italic = true
+            dim = true
+        "#)]);
+        let theme = registry.theme("paper", false).unwrap();
+        assert_eq!(theme.key, Style::new().color(AnsiColors::Blue).on_color(AnsiColors::White).bold().underline());
+        assert_eq!(theme.string, Style::new().color(AnsiColors::BrightGreen).italic().dimmed());
+        assert_eq!(theme.null, Style::new());
+        assert!(registry.names().contains(&"paper".to_string()));
+        assert!(registry.is_user_theme("Paper"));
+    }
+
+    #[test]
+    fn test_user_theme_inherits() {
+        let registry = inline_registry(&[
+            ("deep", "inherits = \"ocean\"\n[key]\nbold = true\n"),
+            ("deeper", "inherits = \"deep\"\n[null]\nfg = \"red\"\n"),
+            ("ocean", "inherits = \"ocean\"\n[number]\nfg = \"yellow\"\n"),
+        ]);
+        let ocean = Theme::new("ocean", false).unwrap();
+        let deeper = registry.theme("deeper", false).unwrap();
+        assert_eq!(deeper.key, ocean.key.bold());
+        assert_eq!(deeper.string, ocean.string);
+        assert_eq!(deeper.null, Style::new().color(AnsiColors::Red));
+
+        let shadowed = registry.theme("ocean", false).unwrap();
+        assert_eq!(shadowed.number, Style::new().color(AnsiColors::Yellow));
+        assert_eq!(shadowed.key, ocean.key);
+    }
+
+    #[test]
+    fn test_user_theme_errors_name_the_field() {
+        let registry = inline_registry(&[("bad", "[punctuation]\nfg = \"purplish\"\n")]);
+        let err = registry.theme("bad", true).unwrap_err().to_string();
+        assert!(err.contains("purplish"), "{}", err);
+        assert!(err.contains("punctuation.fg"), "{}", err);
+
+        for bad in ["#aééa", "#+f+f+f"] {
+            let registry = inline_registry(&[("bad", &format!("[key]\nfg = \"{}\"\n", bad))]);
+            let err = registry.theme("bad", true).unwrap_err();
+            assert!(matches!(err, JsonfizzError::Config(_)), "{}", err);
+            assert!(err.to_string().contains("invalid hex color"), "{}", err);
+        }
+
+        let err = toml::from_str::<ThemeDef>("[key]\ncolour = \"red\"\n").unwrap_err().to_string();
+        assert!(err.contains("colour"), "{}", err);
+
+        let cycle = inline_registry(&[("a", "inherits = \"b\""), ("b", "inherits = \"a\"")]);
+        assert!(cycle.theme("a", false).unwrap_err().to_string().contains("nested too deeply"));
+    }
+
+    #[test]
+    fn test_theme_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("night.toml");
+        std::fs::write(&path, "inherits = \"default\"\n[key]\nfg = \"magenta\"\n").unwrap();
+        let files = BTreeMap::from([("night".to_string(), path)]);
+        let registry = ThemeRegistry::with_files(&BTreeMap::new(), files);
+        let theme = registry.theme("night", false).unwrap();
+        assert_eq!(theme.key, Style::new().color(AnsiColors::Magenta));
+        assert_eq!(theme.string, Theme::new("default", false).unwrap().string);
+    }
+
+    #[test]
+    fn test_color_depth_detection() {
+        assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm-256color")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(Some("24bit"), None), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("tmux-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm-direct")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_parse_extended_colors() {
+        assert_eq!(parse_color("#ff8800", "key.fg"), Ok(ThemeColor::Rgb(255, 136, 0)));
+        assert_eq!(parse_color("#f80", "key.fg"), Ok(ThemeColor::Rgb(255, 136, 0)));
+        assert_eq!(parse_color("208", "key.fg"), Ok(ThemeColor::Fixed(208)));
+        assert!(parse_color("#ff88", "key.fg").unwrap_err().contains("key.fg"));
+        assert!(parse_color("300", "key.bg").unwrap_err().contains("0-255"));
+    }
+
+    #[test]
+    fn test_color_downgrade() {
+        let orange = ThemeColor::Rgb(255, 136, 0);
+        assert_eq!(orange.for_depth(ColorDepth::TrueColor), DynColors::Rgb(255, 136, 0));
+        assert_eq!(orange.for_depth(ColorDepth::Ansi256), DynColors::Xterm(208.into()));
+        assert_eq!(ThemeColor::Rgb(250, 10, 10).for_depth(ColorDepth::Ansi16), DynColors::Ansi(AnsiColors::BrightRed));
+        assert_eq!(ThemeColor::Rgb(128, 128, 128).for_depth(ColorDepth::Ansi256), DynColors::Xterm(244.into()));
+        assert_eq!(ThemeColor::Fixed(21).for_depth(ColorDepth::Ansi16), DynColors::Ansi(AnsiColors::Blue));
+        assert_eq!(ThemeColor::Fixed(9).for_depth(ColorDepth::Ansi16), DynColors::Ansi(AnsiColors::BrightRed));
+    }
+
+    #[test]
+    fn test_color_fallback_list() {
+        let value = "#859900, 106, green";
+        assert_eq!(pick_color(value, "string.fg", ColorDepth::TrueColor), Ok(DynColors::Rgb(0x85, 0x99, 0x00)));
+        assert_eq!(pick_color(value, "string.fg", ColorDepth::Ansi256), Ok(DynColors::Xterm(106.into())));
+        assert_eq!(pick_color(value, "string.fg", ColorDepth::Ansi16), Ok(DynColors::Ansi(AnsiColors::Green)));
+        assert_eq!(pick_color("#ff0000", "key.fg", ColorDepth::Ansi16), Ok(DynColors::Ansi(AnsiColors::BrightRed)));
+        assert!(pick_color("#ff0000, purplish", "key.fg", ColorDepth::TrueColor).unwrap_err().contains("purplish"));
+    }
+
+    #[test]
+    fn test_builtin_themes_stay_distinct_at_every_depth() {
+        for name in BUILTIN_THEMES.iter().filter(|n| **n != "mono") {
+            for background in [Background::Dark, Background::Light] {
+                for depth in [ColorDepth::Ansi16, ColorDepth::Ansi256, ColorDepth::TrueColor] {
+                    let theme = builtin(name).unwrap().for_background(background).build(depth).unwrap();
+                    let styles = [theme.key, theme.string, theme.number, theme.boolean];
+                    for (i, a) in styles.iter().enumerate() {
+                        for b in &styles[i + 1..] {
+                            assert_ne!(a, b, "theme {} has identical token colors at {:?} on {:?}", name, depth, background);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_light_background_variants() {
+        let dark = ThemeRegistry::default().theme("default", false).unwrap();
+        let light = ThemeRegistry::default().with_background(Background::Light).theme("default", false).unwrap();
+        assert_eq!(dark.punctuation, Style::new().color(AnsiColors::White));
+        assert_eq!(light.punctuation, Style::new().color(AnsiColors::Black));
+        assert_eq!(light.string, dark.string);
+
+        let registry = inline_registry(&[
+            ("paper", "inherits = \"default\"\n[punctuation]\nfg = \"red\"\n[dark.key]\nfg = \"bright-yellow\"\n"),
+            ("broken", "[light]\ninherits = \"ocean\"\n"),
+        ]);
+        let light = registry.clone().with_background(Background::Light).theme("paper", false).unwrap();
+        assert_eq!(light.punctuation, Style::new().color(AnsiColors::Red));
+        assert_eq!(light.key, Style::new().color(AnsiColors::Blue));
+        let dark = registry.clone().theme("paper", false).unwrap();
+        assert_eq!(dark.key, Style::new().color(AnsiColors::BrightYellow));
+        assert!(registry.theme("broken", false).unwrap_err().to_string().contains("[light]"));
+    }
+
+    #[test]
+    fn test_background_from_luminance() {
+        assert_eq!(Background::from_luminance(0.9), Background::Light);
+        assert_eq!(Background::from_luminance(0.1), Background::Dark);
+    }
+}
@@ -0,0 +1,645 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::{Frame, Terminal};
+use serde_json::Value;
+use crate::error::JsonfizzError;
+use crate::formatter::counted;
+use crate::path::{format_path, parse_path, JsonPath, PathSegment};
+use crate::theme::{TermColor, TextStyle, Theme, TokenKind};
+
+/// One line of the tree as currently unfolded.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    path: JsonPath,
+    depth: usize,
+    kind: RowKind,
+    /// No comma follows: the last member of its container, or the root.
+    last: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RowKind {
+    /// The line opening an object or array; collapsed and empty containers
+    /// fit on this one line.
+    Open { collapsed: bool, len: usize },
+    Close,
+    Scalar,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Mode {
+    Normal,
+    Search(String),
+    Goto(String),
+}
+
+/// State of the `--interactive` viewer, kept apart from the terminal so it
+/// can be driven by key events and drawn on any ratatui backend.
+pub struct Viewer {
+    root: Value,
+    collapsed: HashSet<JsonPath>,
+    rows: Vec<Row>,
+    cursor: usize,
+    scroll: usize,
+    /// Rows that fit on screen at the last draw, for paging.
+    height: usize,
+    mode: Mode,
+    matches: Vec<JsonPath>,
+    match_index: usize,
+    message: Option<String>,
+    clipboard: Option<String>,
+    quit: bool,
+}
+
+impl Viewer {
+    pub fn new(root: Value) -> Self {
+        let mut viewer = Viewer {
+            root,
+            collapsed: HashSet::new(),
+            rows: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            height: 1,
+            mode: Mode::Normal,
+            matches: Vec::new(),
+            match_index: 0,
+            message: None,
+            clipboard: None,
+            quit: false,
+        };
+        viewer.rebuild();
+        viewer
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Text waiting to be sent to the clipboard, if a copy was requested.
+    pub fn take_clipboard(&mut self) -> Option<String> {
+        self.clipboard.take()
+    }
+
+    /// The path of the node under the cursor.
+    pub fn current_path(&self) -> &JsonPath {
+        &self.rows[self.cursor].path
+    }
+
+    fn rebuild(&mut self) {
+        let mut rows = Vec::new();
+        push_rows(&self.root, &mut Vec::new(), 0, true, &self.collapsed, &mut rows);
+        self.rows = rows;
+        self.cursor = self.cursor.min(self.rows.len() - 1);
+    }
+
+    /// Move the cursor to the opening row of `path`.
+    fn select(&mut self, path: &JsonPath) {
+        if let Some(index) = self.rows.iter().position(|row| &row.path == path && row.kind != RowKind::Close) {
+            self.cursor = index;
+        }
+    }
+
+    /// Unfold every container around `path` and put the cursor on it.
+    fn reveal(&mut self, path: &JsonPath) {
+        for len in 0..path.len() {
+            self.collapsed.remove(&path[..len]);
+        }
+        self.rebuild();
+        self.select(path);
+    }
+
+    fn set_collapsed(&mut self, path: JsonPath, collapsed: bool) {
+        if collapsed {
+            self.collapsed.insert(path.clone());
+        } else {
+            self.collapsed.remove(&path);
+        }
+        self.rebuild();
+        self.select(&path);
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let last = self.rows.len() as isize - 1;
+        self.cursor = (self.cursor as isize + delta).clamp(0, last) as usize;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        self.message = None;
+        match std::mem::replace(&mut self.mode, Mode::Normal) {
+            Mode::Normal => self.handle_normal_key(key),
+            Mode::Search(input) => {
+                if let Some(input) = self.edit_input(input, key) {
+                    self.mode = Mode::Search(input);
+                } else if key.code == KeyCode::Enter {
+                    self.search_submitted();
+                }
+            }
+            Mode::Goto(input) => {
+                if let Some(input) = self.edit_input(input, key) {
+                    self.mode = Mode::Goto(input);
+                } else if key.code == KeyCode::Enter {
+                    self.goto_submitted();
+                }
+            }
+        }
+    }
+
+    /// Apply a key to a prompt's input. Returns the input while the prompt
+    /// stays open; on Enter it is left in `message` for the caller.
+    fn edit_input(&mut self, mut input: String, key: KeyEvent) -> Option<String> {
+        match key.code {
+            KeyCode::Enter => {
+                self.message = Some(input);
+                None
+            }
+            KeyCode::Esc => None,
+            KeyCode::Backspace => {
+                input.pop();
+                Some(input)
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+                Some(input)
+            }
+            _ => Some(input),
+        }
+    }
+
+    fn search_submitted(&mut self) {
+        let query = self.message.take().unwrap_or_default().to_lowercase();
+        self.matches.clear();
+        self.match_index = 0;
+        if !query.is_empty() {
+            collect_matches(&self.root, &mut Vec::new(), &query, &mut self.matches);
+        }
+        if self.matches.is_empty() {
+            self.message = Some(format!("no match for '{}'", query));
+        } else {
+            self.show_match(0);
+        }
+    }
+
+    fn show_match(&mut self, index: usize) {
+        self.match_index = index;
+        let path = self.matches[index].clone();
+        self.reveal(&path);
+        self.message = Some(format!("match {}/{}", index + 1, self.matches.len()));
+    }
+
+    fn goto_submitted(&mut self) {
+        let input = self.message.take().unwrap_or_default();
+        match parse_path(input.trim()) {
+            Ok(path) if lookup(&self.root, &path).is_some() => self.reveal(&path),
+            Ok(_) => self.message = Some(format!("no value at '{}'", input.trim())),
+            Err(e) => self.message = Some(e.to_string()),
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) {
+        let row = self.rows[self.cursor].clone();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(self.height as isize),
+            KeyCode::PageUp => self.move_by(-(self.height as isize)),
+            KeyCode::Home | KeyCode::Char('g') => self.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => self.cursor = self.rows.len() - 1,
+            KeyCode::Enter | KeyCode::Char(' ') => match row.kind {
+                RowKind::Open { collapsed, len } if len > 0 => self.set_collapsed(row.path, !collapsed),
+                RowKind::Close => self.set_collapsed(row.path, true),
+                _ => {}
+            },
+            KeyCode::Right | KeyCode::Char('l') => {
+                if let RowKind::Open { collapsed: true, .. } = row.kind {
+                    self.set_collapsed(row.path, false);
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') => match row.kind {
+                RowKind::Open { collapsed: false, len } if len > 0 && row.depth > 0 => self.set_collapsed(row.path, true),
+                RowKind::Close => self.select(&row.path),
+                _ if !row.path.is_empty() => self.select(&row.path[..row.path.len() - 1].to_vec()),
+                _ => {}
+            },
+            KeyCode::Char('E') => {
+                self.collapsed.clear();
+                self.rebuild();
+                self.select(&row.path);
+            }
+            KeyCode::Char('C') => {
+                collect_containers(&self.root, &mut Vec::new(), &mut self.collapsed);
+                self.collapsed.remove(&Vec::new());
+                self.rebuild();
+                let top = row.path.iter().take(1).cloned().collect();
+                self.select(&top);
+            }
+            KeyCode::Char('/') => self.mode = Mode::Search(String::new()),
+            KeyCode::Char(':') => self.mode = Mode::Goto(String::new()),
+            KeyCode::Char('n') if !self.matches.is_empty() => {
+                self.show_match((self.match_index + 1) % self.matches.len());
+            }
+            KeyCode::Char('N') if !self.matches.is_empty() => {
+                self.show_match((self.match_index + self.matches.len() - 1) % self.matches.len());
+            }
+            KeyCode::Char('y') => {
+                self.clipboard = Some(format_path(&row.path));
+                self.message = Some("copied path".to_string());
+            }
+            KeyCode::Char('Y') => {
+                let value = lookup(&self.root, &row.path).map(|v| v.to_string()).unwrap_or_default();
+                self.clipboard = Some(value);
+                self.message = Some("copied value".to_string());
+            }
+            _ => {}
+        }
+    }
+
+    /// Draw the tree and the status bar, scrolling to keep the cursor in view.
+    pub fn render(&mut self, frame: &mut Frame, theme: &Theme) {
+        let [body, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        self.height = (body.height as usize).max(1);
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + self.height {
+            self.scroll = self.cursor + 1 - self.height;
+        }
+
+        let lines: Vec<Line> = self.rows[self.scroll..]
+            .iter()
+            .take(self.height)
+            .enumerate()
+            .map(|(i, row)| {
+                let line = Line::from(self.row_spans(row, theme));
+                if self.scroll + i == self.cursor {
+                    line.style(Style::new().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), body);
+
+        let left = match (&self.mode, &self.message) {
+            (Mode::Search(input), _) => format!("/{}", input),
+            (Mode::Goto(input), _) => format!(":{}", input),
+            (Mode::Normal, Some(message)) => message.clone(),
+            (Mode::Normal, None) => match format_path(self.current_path()) {
+                path if path.is_empty() => "(root)".to_string(),
+                path => path,
+            },
+        };
+        let right = format!("{}/{}", self.cursor + 1, self.rows.len());
+        let gap = (status.width as usize).saturating_sub(left.chars().count() + right.chars().count() + 2);
+        let bar = format!(" {}{}{} ", left, " ".repeat(gap), right);
+        frame.render_widget(Paragraph::new(bar).style(Style::new().add_modifier(Modifier::REVERSED)), status);
+    }
+
+    fn row_spans(&self, row: &Row, theme: &Theme) -> Vec<Span<'static>> {
+        let span = |text: String, kind: TokenKind| Span::styled(text, ratatui_style(theme, kind));
+        let mut spans = vec![Span::raw("  ".repeat(row.depth))];
+        if row.kind != RowKind::Close {
+            if let Some(PathSegment::Key(key)) = row.path.last() {
+                spans.push(span(Value::String(key.clone()).to_string(), TokenKind::Key));
+                spans.push(span(": ".to_string(), TokenKind::Colon));
+            }
+        }
+        let value = lookup(&self.root, &row.path);
+        let (open, close) = match value {
+            Some(Value::Array(_)) => ("[", "]"),
+            _ => ("{", "}"),
+        };
+        let bracket = TokenKind::Bracket(row.depth);
+        match row.kind {
+            RowKind::Open { len: 0, .. } => spans.push(span(format!("{}{}", open, close), bracket)),
+            RowKind::Open { collapsed: true, len } => {
+                let unit = if open == "[" { "item" } else { "key" };
+                spans.push(span(open.to_string(), bracket));
+                spans.push(span(format!("… {}", counted(len, unit)), TokenKind::Annotation));
+                spans.push(span(close.to_string(), bracket));
+            }
+            RowKind::Open { .. } => spans.push(span(open.to_string(), bracket)),
+            RowKind::Close => spans.push(span(close.to_string(), bracket)),
+            RowKind::Scalar => {
+                if let Some(value) = value {
+                    spans.push(span(value.to_string(), scalar_kind(value)));
+                }
+            }
+        }
+        let open_expanded = matches!(row.kind, RowKind::Open { collapsed: false, len } if len > 0);
+        if !row.last && !open_expanded {
+            spans.push(span(",".to_string(), TokenKind::Comma));
+        }
+        spans
+    }
+
+    fn run_loop<B: Backend + Write>(&mut self, terminal: &mut Terminal<B>, theme: &Theme) -> Result<(), JsonfizzError> {
+        loop {
+            terminal.draw(|frame| self.render(frame, theme))?;
+            if let Some(text) = self.take_clipboard() {
+                let backend = terminal.backend_mut();
+                write!(backend, "{}", osc52(&text))?;
+                Write::flush(backend)?;
+            }
+            if self.quit {
+                return Ok(());
+            }
+            if let Event::Key(key) = event::read()? {
+                self.handle_key(key);
+            }
+        }
+    }
+}
+
+/// Browse `value` full-screen until the user quits. Keys are read from the
+/// terminal even when the document came in on stdin.
+pub fn run(value: Value, theme: &Theme) -> Result<(), JsonfizzError> {
+    if !atty::is(atty::Stream::Stdout) {
+        return Err(JsonfizzError::Data("--interactive needs a terminal on stdout".to_string()));
+    }
+    let mut viewer = Viewer::new(value);
+    let _screen = Screen::enter()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    viewer.run_loop(&mut terminal, theme)
+}
+
+/// Raw mode and the alternate screen for as long as the value lives.
+/// Leaving them on drop restores the terminal on every way out of [`run`],
+/// including errors and panics.
+struct Screen;
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        let screen = Screen;
+        crossterm::execute!(io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(io::stdout(), crossterm::terminal::LeaveAlternateScreen);
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+fn push_rows(value: &Value, path: &mut JsonPath, depth: usize, last: bool, collapsed: &HashSet<JsonPath>, rows: &mut Vec<Row>) {
+    let len = match value {
+        Value::Object(map) => map.len(),
+        Value::Array(arr) => arr.len(),
+        _ => {
+            rows.push(Row { path: path.clone(), depth, kind: RowKind::Scalar, last });
+            return;
+        }
+    };
+    let is_collapsed = len > 0 && collapsed.contains(path);
+    rows.push(Row { path: path.clone(), depth, kind: RowKind::Open { collapsed: is_collapsed, len }, last });
+    if len == 0 || is_collapsed {
+        return;
+    }
+    let mut child = |segment: PathSegment, value: &Value, index: usize, path: &mut JsonPath| {
+        path.push(segment);
+        push_rows(value, path, depth + 1, index + 1 == len, collapsed, rows);
+        path.pop();
+    };
+    match value {
+        Value::Object(map) => {
+            for (i, (key, value)) in map.iter().enumerate() {
+                child(PathSegment::Key(key.clone()), value, i, path);
+            }
+        }
+        Value::Array(arr) => {
+            for (i, value) in arr.iter().enumerate() {
+                child(PathSegment::Index(i), value, i, path);
+            }
+        }
+        _ => {}
+    }
+    rows.push(Row { path: path.clone(), depth, kind: RowKind::Close, last });
+}
+
+/// Paths of every node whose key or scalar value contains `query`, in
+/// document order. `query` is already lowercase.
+fn collect_matches(value: &Value, path: &mut JsonPath, query: &str, matches: &mut Vec<JsonPath>) {
+    let key_matches = matches!(path.last(), Some(PathSegment::Key(key)) if key.to_lowercase().contains(query));
+    let value_matches = match value {
+        Value::String(s) => s.to_lowercase().contains(query),
+        Value::Object(_) | Value::Array(_) => false,
+        scalar => scalar.to_string().contains(query),
+    };
+    if key_matches || value_matches {
+        matches.push(path.clone());
+    }
+    for_each_child(value, path, |child, path| collect_matches(child, path, query, matches));
+}
+
+fn collect_containers(value: &Value, path: &mut JsonPath, containers: &mut HashSet<JsonPath>) {
+    if matches!(value, Value::Object(_) | Value::Array(_)) {
+        containers.insert(path.clone());
+    }
+    for_each_child(value, path, |child, path| collect_containers(child, path, containers));
+}
+
+fn for_each_child(value: &Value, path: &mut JsonPath, mut f: impl FnMut(&Value, &mut JsonPath)) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                path.push(PathSegment::Key(key.clone()));
+                f(child, path);
+                path.pop();
+            }
+        }
+        Value::Array(arr) => {
+            for (i, child) in arr.iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                f(child, path);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &[PathSegment]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, segment| match segment {
+        PathSegment::Key(key) => value.get(key),
+        PathSegment::Index(index) => value.get(index),
+    })
+}
+
+fn scalar_kind(value: &Value) -> TokenKind {
+    match value {
+        Value::String(s) => crate::formatter::string_kind(s),
+        Value::Number(n) if n.is_f64() => TokenKind::Float,
+        Value::Number(_) => TokenKind::Integer,
+        Value::Bool(_) => TokenKind::Bool,
+        _ => TokenKind::Null,
+    }
+}
+
+/// The theme's style for `kind` as a ratatui style.
+fn ratatui_style(theme: &Theme, kind: TokenKind) -> Style {
+    let attrs = TextStyle::from_style(theme.style(kind));
+    let color = |c: TermColor| match c {
+        TermColor::Indexed(n) => Color::Indexed(n),
+        TermColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    };
+    let mut style = Style::new();
+    if let Some(fg) = attrs.fg {
+        style = style.fg(color(fg));
+    }
+    if let Some(bg) = attrs.bg {
+        style = style.bg(color(bg));
+    }
+    for (on, modifier) in [
+        (attrs.bold, Modifier::BOLD),
+        (attrs.dim, Modifier::DIM),
+        (attrs.italic, Modifier::ITALIC),
+        (attrs.underline, Modifier::UNDERLINED),
+    ] {
+        if on {
+            style = style.add_modifier(modifier);
+        }
+    }
+    style
+}
+
+/// The OSC 52 sequence asking the terminal to put `text` on the clipboard.
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use serde_json::json;
+
+    fn press(viewer: &mut Viewer, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                c => KeyCode::Char(c),
+            };
+            viewer.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    fn screen(viewer: &mut Viewer, theme: &Theme, width: u16, height: u16) -> (Vec<String>, ratatui::buffer::Buffer) {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|frame| viewer.render(frame, theme)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let lines = (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string())
+            .collect();
+        (lines, buffer)
+    }
+
+    fn sample() -> Value {
+        json!({"name": "fizz", "items": [{"id": 1}, {"id": 2, "tags": ["a", "b"]}], "meta": {"ok": true}})
+    }
+
+    #[test]
+    fn test_folding() {
+        let theme = Theme::plain();
+        let mut viewer = Viewer::new(sample());
+        let (lines, _) = screen(&mut viewer, &theme, 40, 24);
+        assert_eq!(lines[0], "{");
+        assert_eq!(lines[1], "  \"items\": [");
+        assert_eq!(viewer.rows.len(), 18);
+
+        press(&mut viewer, "j ");
+        let (lines, _) = screen(&mut viewer, &theme, 40, 24);
+        assert_eq!(lines[1], "  \"items\": [… 2 items],");
+        assert_eq!(lines[2], "  \"meta\": {");
+        assert_eq!(lines[23], " items                              2/7");
+
+        press(&mut viewer, "l");
+        assert_eq!(viewer.rows.len(), 18);
+        press(&mut viewer, "C");
+        let (lines, _) = screen(&mut viewer, &theme, 40, 24);
+        assert_eq!(&lines[..5], ["{", "  \"items\": [… 2 items],", "  \"meta\": {… 1 key},", "  \"name\": \"fizz\"", "}"]);
+        press(&mut viewer, "E");
+        assert_eq!(viewer.rows.len(), 18);
+    }
+
+    #[test]
+    fn test_search_reveals_matches() {
+        let theme = Theme::plain();
+        let mut viewer = Viewer::new(sample());
+        press(&mut viewer, "C/TAG\n");
+        assert_eq!(format_path(viewer.current_path()), "items[1].tags");
+        let (lines, _) = screen(&mut viewer, &theme, 40, 10);
+        assert!(lines[9].starts_with(" match 1/1"));
+        press(&mut viewer, "/2\n");
+        assert_eq!(format_path(viewer.current_path()), "items[1].id");
+        press(&mut viewer, "/zzz\n");
+        assert_eq!(viewer.message.as_deref(), Some("no match for 'zzz'"));
+    }
+
+    #[test]
+    fn test_goto_path_and_scrolling() {
+        let theme = Theme::plain();
+        let mut viewer = Viewer::new(sample());
+        press(&mut viewer, ":meta.ok\n");
+        assert_eq!(format_path(viewer.current_path()), "meta.ok");
+        let (lines, _) = screen(&mut viewer, &theme, 40, 5);
+        assert_eq!(lines[3], "    \"ok\": true");
+        assert_eq!(lines[4], " meta.ok                          15/18");
+        press(&mut viewer, ":items[9]\n");
+        assert_eq!(viewer.message.as_deref(), Some("no value at 'items[9]'"));
+    }
+
+    #[test]
+    fn test_copy_with_osc52() {
+        let mut viewer = Viewer::new(sample());
+        press(&mut viewer, ":items[1].tags\ny");
+        assert_eq!(viewer.take_clipboard().as_deref(), Some("items[1].tags"));
+        press(&mut viewer, "Y");
+        assert_eq!(viewer.take_clipboard().as_deref(), Some(r#"["a","b"]"#));
+        assert_eq!(viewer.take_clipboard(), None);
+        assert_eq!(osc52("hello"), "\x1b]52;c;aGVsbG8=\x07");
+        assert_eq!(base64_encode(b"hi!?"), "aGkhPw==");
+    }
+
+    #[test]
+    fn test_uses_theme_colors() {
+        let theme = crate::theme::ThemeRegistry::default()
+            .with_color_depth(crate::theme::ColorDepth::TrueColor)
+            .theme("default", false)
+            .unwrap();
+        let mut viewer = Viewer::new(json!({"k": "v"}));
+        press(&mut viewer, "j");
+        let (_, buffer) = screen(&mut viewer, &theme, 20, 4);
+        assert_eq!(buffer[(2, 1)].style().fg, ratatui_style(&theme, TokenKind::Key).fg);
+        assert!(buffer[(2, 1)].style().fg.is_some());
+        assert!(buffer[(2, 1)].style().add_modifier.contains(Modifier::REVERSED));
+        assert_eq!(buffer[(7, 1)].style().fg, ratatui_style(&theme, TokenKind::String).fg);
+    }
+}